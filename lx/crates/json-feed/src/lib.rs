@@ -18,8 +18,10 @@ pub use v1_1::{AuthorOptions, Builder as JSONFeedBuilder, FeedItem, JSONFeed};
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
 pub enum Version {
    /// The feed is [v1](https://jsonfeed.org/version/1).
+   #[serde(rename = "https://jsonfeed.org/version/1")]
    V1_0,
    /// The feed is [v1.1](https://jsonfeed.org/version/1.1).
+   #[serde(rename = "https://jsonfeed.org/version/1.1")]
    V1_1,
 }
 
@@ -109,4 +111,12 @@ mod tests {
          Err(format!("Bad JSON Feed `version` field: '{}'", bad))
       );
    }
+
+   #[test]
+   fn serializes_version_as_url() {
+      assert_eq!(
+         serde_json::to_string(&Version::V1_1).unwrap(),
+         r#""https://jsonfeed.org/version/1.1""#
+      );
+   }
 }
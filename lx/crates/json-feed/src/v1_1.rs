@@ -23,19 +23,23 @@ pub struct JSONFeed {
    /// this should be considered as required. But it may not make sense in the
    /// case of a file created on a desktop computer, when that file is not
    /// shared or is shared only privately.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub home_page_url: Option<String>,
 
    /// (optional but strongly recommended) The URL of the feed, and serves as
    /// the unique identifier for the feed. As with `home_page_url`, this should
    /// be considered required for feeds on the public web.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub feed_url: Option<String>,
 
    /// Provides more detail, beyond the `title`, on what the feed is about. A
    /// feed reader may display this text.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub description: Option<String>,
 
    /// Description of the purpose of the feed. This is for the use of people
    /// looking at the raw JSON, and should be ignored by feed readers.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub user_comment: Option<String>,
 
    /// The URL of a feed that provides the next n items, where n is determined
@@ -43,6 +47,7 @@ pub struct JSONFeed {
    /// that reader software is not required to use it and probably won’t use it
    /// very often. `next_url` must not be the same as `feed_url`, and it must
    /// not be the same as a previous `next_url` (to avoid infinite loops).
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub next_url: Option<String>,
 
    /// The URL of an image for the feed suitable to be used in a timeline, much
@@ -50,6 +55,7 @@ pub struct JSONFeed {
    /// large ― such as 512 x 512 ― so that it can be scaled-down and so that it
    /// can look good on retina displays. It should use transparency where
    /// appropriate, since it may be rendered on a non-white background.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub icon: Option<String>,
 
    /// The URL of an image for the feed suitable to be used in a source list.
@@ -57,11 +63,13 @@ pub struct JSONFeed {
    /// (so that it can look good on retina displays). As with `icon`, this
    /// image should use transparency where appropriate, since it may be
    /// rendered on a non-white background.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub favicon: Option<String>,
 
    /// Specifies the feed author. The author object has several members. These
    /// are all optional ― but if you provide an author object, then at least
    /// one is required.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub author: Option<Author>,
 
    /// Says whether or not the feed is finished ― that is, whether or not it
@@ -69,6 +77,7 @@ pub struct JSONFeed {
    /// instance of the Olympics, could expire. If the value is true, then it’s
    /// expired. Any other value, or the absence of expired, means the feed may
    /// continue to update.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub expired: Option<bool>,
 
    /// Describes endpoints that can be used to subscribe to real-time
@@ -78,6 +87,7 @@ pub struct JSONFeed {
    ///
    /// [“Subscribing to Real-time Notifications”]:
    /// https://jsonfeed.org/version/1#subscribing-to-real-time-notifications
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub hubs: Option<Vec<Hub>>,
 
    /** The items in the feed. */
@@ -100,14 +110,17 @@ pub struct FeedItem {
 
    /// The URL of the resource described by the item. It’s the permalink. This
    /// may be the same as the id ― but should be present regardless.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub url: Option<String>,
 
    /// The URL of a page elsewhere. This is especially useful for linkblogs. If
    /// `url` links to where you’re talking about a thing, then `external_url`
    /// links to the thing you’re talking about.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub external_url: Option<String>,
 
    /// Plain text. Microblog items in particular may omit titles.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub title: Option<String>,
 
    /// The plain text of the item.
@@ -116,6 +129,7 @@ pub struct FeedItem {
    /// Twitter-like service might use `content_text`, while a blog might use
    /// `content_html`. Use whichever makes sense for your resource. (It doesn’t
    /// even have to be the same for each item in a feed.)
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub content_text: Option<String>,
 
    /// The HTML of the item. Important: the only place HTML is allowed in this
@@ -125,17 +139,20 @@ pub struct FeedItem {
    /// Twitter-like service might use `content_text`, while a blog might use
    /// `content_html`. Use whichever makes sense for your resource. (It doesn’t
    /// even have to be the same for each item in a feed.)
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub content_html: Option<String>,
 
    /// A plain text sentence or two describing the item. This might be
    /// presented in a timeline, for instance, where a detail view would display
    /// all of `content_html` or `content_text`.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub summary: Option<String>,
 
    /// The URL of the main image for the item. This image may also appear in
    /// the `content_html` ― if so, it’s a hint to the feed reader that this is
    /// the main, featured image. Feed readers may use the image as a preview
    /// (probably resized as a thumbnail and placed in a timeline).
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub image: Option<String>,
 
    /// The URL of an image to use as a banner. Some blogging systems (such as
@@ -145,30 +162,36 @@ pub struct FeedItem {
    /// top of the detail view, possibly with the title overlaid.
    ///
    /// [Medium]: https://medium.com/
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub banner_image: Option<String>,
 
    /// Specifies the date in [RFC 3339](https://tools.ietf.org/html/rfc3339)
    /// format. (Example: `2010-02-07T14:04:00-05:00`.)
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub date_published: Option<String>,
 
    /// Specifies the modification date in [RFC 3339] format.
    ///
    /// [RFC 3339]: https://www.ietf.org/rfc/rfc3339.txt
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub date_modified: Option<String>,
 
    /// The same structure as the top-level `author`. If not specified in an
    /// item, then the top-level `author`, if present, is the author of the
    /// item.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub author: Option<Author>,
 
    /// Any plain text values you want. Tags tend to be just one word, but they
    /// may be anything. Note: they are not the equivalent of Twitter hashtags.
    /// Some blogging systems and other feed formats call these categories.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub tags: Option<Vec<String>>,
 
    /// An individual item may have one or more attachments. List related
    /// resources. Podcasts, for instance, would include an attachment that’s an
    /// audio or video file.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub attachments: Option<Vec<Attachment>>,
 }
 
@@ -178,6 +201,7 @@ pub struct Attachment {
    // number, which is... an odd type.
    /// Specifies how long it takes to listen to or watch, when played at normal
    /// speed
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub duration_in_seconds: Option<f64>,
 
    /// Specifies the type of the attachment, such as “audio/mpeg.”
@@ -186,6 +210,7 @@ pub struct Attachment {
    // As above. No, this doesn't make any sense in the usual case, but we're
    // generating JSON. :shrug:
    /// Specifies how large the file is.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub size_in_bytes: Option<f64>,
 
    /// A name for the attachment. Important: if there are multiple attachments,
@@ -193,6 +218,7 @@ pub struct Attachment {
    /// then they are considered as alternate representations of the same thing.
    /// In this way a podcaster, for instance, might provide an audio recording
    /// in different formats.
+   #[serde(skip_serializing_if = "Option::is_none")]
    pub title: Option<String>,
 
    /// Specifies the location of the attachment.
@@ -10,10 +10,12 @@
 //!     - Emit footnotes.
 
 mod first_pass;
+mod plain;
 mod second_pass;
 
 use std::collections::HashMap;
 use std::fmt::Debug;
+use std::ops::Range;
 
 use lazy_static::lazy_static;
 pub use pulldown_cmark::Options;
@@ -33,21 +35,37 @@ type FootnoteDefinitions<'e> = HashMap<CowStr<'e>, Vec<Event<'e>>>;
 #[derive(Error, Debug)]
 pub enum PrepareError {
    #[error("tried to use TOML for metadata")]
-   UsedToml,
+   UsedToml { range: Range<usize> },
 
    #[error("failed to extract metadata section")]
    MetadataExtraction,
 
    #[error("could not prepare Markdown: {state} is invalid in {context}")]
-   State { state: String, context: String },
+   State {
+      state: String,
+      context: String,
+      range: Range<usize>,
+   },
 
    #[error("could not prepare Markdown content section")]
    Content {
-      #[from]
       source: first_pass::Error,
+      range: Range<usize>,
    },
 }
 
+impl PrepareError {
+   /// The byte range in the source where the problem is, when it is known.
+   pub fn range(&self) -> Option<Range<usize>> {
+      match self {
+         PrepareError::UsedToml { range }
+         | PrepareError::State { range, .. }
+         | PrepareError::Content { range, .. } => Some(range.clone()),
+         PrepareError::MetadataExtraction => None,
+      }
+   }
+}
+
 // The structure here lets the caller have access to the extracted metadata
 // string (we do not need the parsed or rendered metadata) during the
 // preparation pass, but only provides the `ToRender` type opaquely, so that it
@@ -76,6 +94,16 @@ pub enum Error {
    },
 }
 
+impl Error {
+   /// The byte range in the source where the problem is, when it is known.
+   pub fn range(&self) -> Option<Range<usize>> {
+      match self {
+         Error::Prepare { source } => source.range(),
+         Error::Render { .. } => None,
+      }
+   }
+}
+
 lazy_static! {
    static ref OPTIONS: Options = {
       let mut opts = Options::all();
@@ -96,6 +124,11 @@ impl Markdown {
       }
    }
 
+   /// The syntaxes used for highlighting code blocks.
+   pub fn syntax_set(&self) -> &SyntaxSet {
+      &self.syntax_set
+   }
+
    pub fn render(
       &self,
       src: &str,
@@ -119,7 +152,7 @@ impl Markdown {
          footnote_definitions,
       } = to_render;
 
-      let events = second_pass(
+      let (events, plain) = second_pass(
          footnote_definitions,
          &self.syntax_set,
          first_pass_events,
@@ -127,10 +160,10 @@ impl Markdown {
       )
       .map_err(RenderError::from)?;
 
-      let mut content = String::new();
-      html::push_html(&mut content, events);
+      let mut html = String::new();
+      html::push_html(&mut html, events);
 
-      Ok(Rendered(content))
+      Ok(Rendered { html, plain })
    }
 }
 
@@ -141,20 +174,25 @@ pub fn prepare(src: &str) -> Result<Prepared<'_>, Error> {
 
    // TODO: rewrite all these `bad_prepare_state` calls into actual specific errors from
    // the enum above!
-   for event in parser {
+   for (event, range) in parser.into_offset_iter() {
+      let in_content = |source| PrepareError::Content {
+         source,
+         range: range.clone(),
+      };
+
       match event {
          Event::Start(Tag::MetadataBlock(kind)) => match state {
             FirstPass::Initial(initial) => {
                state = FirstPass::ExtractingMetadata(initial.parsing_metadata(kind))
             }
-            _ => return bad_prepare_state(&event, &state),
+            _ => return bad_prepare_state(&event, &state, range),
          },
 
          Event::End(TagEnd::MetadataBlock(_)) => match state {
             FirstPass::ExtractedMetadata(metadata) => {
                state = FirstPass::Content(metadata.start_content())
             }
-            _ => return bad_prepare_state(&event, &state),
+            _ => return bad_prepare_state(&event, &state, range),
          },
 
          Event::Text(ref text) => match state {
@@ -168,35 +206,38 @@ pub fn prepare(src: &str) -> Result<Prepared<'_>, Error> {
                }
 
                MetadataBlockKind::PlusesStyle => {
-                  return Err(Error::from(PrepareError::UsedToml));
+                  return Err(Error::from(PrepareError::UsedToml { range }));
                }
             },
 
             FirstPass::Content(ref mut content) => {
-               content.handle(event).map_err(PrepareError::from)?
+               content.handle(event).map_err(in_content)?
             }
 
-            _ => return bad_prepare_state(&event, &state),
+            _ => return bad_prepare_state(&event, &state, range),
          },
 
          other => match state {
             FirstPass::Initial(initial) => {
                let mut content = initial.start_content();
-               content.handle(other).map_err(PrepareError::from)?;
+               content.handle(other).map_err(in_content)?;
                state = FirstPass::Content(content);
             }
 
             FirstPass::Content(ref mut content) => {
-               content.handle(other).map_err(PrepareError::from)?
+               content.handle(other).map_err(in_content)?
             }
 
-            _ => return bad_prepare_state(&other, &state),
+            _ => return bad_prepare_state(&other, &state, range),
          },
       }
    }
 
    let (metadata, first_pass_events, footnote_definitions) =
-      state.finalize().map_err(PrepareError::from)?;
+      state.finalize().map_err(|source| PrepareError::Content {
+         source,
+         range: src.len()..src.len(),
+      })?;
 
    Ok(Prepared {
       metadata_src: metadata.map(|m| m.to_string()),
@@ -214,23 +255,36 @@ pub struct RenderError {
    source: second_pass::Error,
 }
 
-/// The result of successfully rendering content: HTML. It can be extracted via
-/// the `.html()` method.
+/// The result of successfully rendering content: HTML, along with the same content as
+/// plain text, without any HTML or Markdown syntax. They can be extracted via the
+/// `.html()` and `.plain()` methods.
 #[derive(Debug, Serialize, Deserialize)]
-#[repr(transparent)]
-pub struct Rendered(String);
+pub struct Rendered {
+   html: String,
+   plain: String,
+}
 
 impl Rendered {
    #[inline(always)]
    pub fn html(&self) -> &str {
-      self.0.as_str()
+      self.html.as_str()
+   }
+
+   #[inline(always)]
+   pub fn plain(&self) -> &str {
+      self.plain.as_str()
    }
 }
 
-fn bad_prepare_state<T>(state: &impl Debug, context: &impl Debug) -> Result<T, Error> {
+fn bad_prepare_state<T>(
+   state: &impl Debug,
+   context: &impl Debug,
+   range: Range<usize>,
+) -> Result<T, Error> {
    Err(Error::from(PrepareError::State {
       state: format!("{state:?}"),
       context: format!("{context:?}"),
+      range,
    }))
 }
 
@@ -0,0 +1,91 @@
+//! Plain text versions of rendered content, for places which cannot take HTML, like
+//! meta descriptions, feed summaries, and `content_text` in JSON Feed.
+//!
+//! The text is collected while the second pass emits its events, so it is whatever the
+//! HTML shows as text, including smart punctuation and rewritten text, minus all markup.
+
+use pulldown_cmark::{Event, Tag, TagEnd};
+
+#[derive(Debug, Default)]
+pub(super) struct Plain {
+   text: String,
+   /// What to put between the text so far and the next text, if any comes.
+   separator: Option<Separator>,
+   /// How many images deep the current event is. Alt text does not read as part of the
+   /// surrounding text, so it is left out.
+   in_image: usize,
+}
+
+/// Ordered so that the larger of two separators between the same text wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Separator {
+   Space,
+   Line,
+   Block,
+}
+
+impl Plain {
+   pub(super) fn text(&mut self, text: &str) {
+      if self.in_image > 0 || text.is_empty() {
+         return;
+      }
+
+      if let Some(separator) = self.separator.take()
+         && !self.text.is_empty()
+      {
+         self.text.push_str(match separator {
+            Separator::Space => " ",
+            Separator::Line => "\n",
+            Separator::Block => "\n\n",
+         });
+      }
+
+      self.text.push_str(text);
+   }
+
+   /// Account for an event other than text which has been rewritten or highlighted.
+   pub(super) fn event(&mut self, event: &Event) {
+      match event {
+         Event::Text(text) | Event::Code(text) => self.text(text),
+         Event::InlineMath(math) | Event::DisplayMath(math) => self.text(math),
+         Event::SoftBreak => self.separate(Separator::Space),
+         Event::HardBreak => self.separate(Separator::Line),
+
+         Event::Start(Tag::Image { .. }) => self.in_image += 1,
+         Event::End(TagEnd::Image) => self.in_image = self.in_image.saturating_sub(1),
+
+         Event::End(
+            TagEnd::Item | TagEnd::TableHead | TagEnd::TableRow | TagEnd::DefinitionListTitle,
+         ) => self.separate(Separator::Line),
+         Event::End(TagEnd::TableCell) => self.separate(Separator::Space),
+         Event::Rule
+         | Event::End(
+            TagEnd::Paragraph
+            | TagEnd::Heading(_)
+            | TagEnd::BlockQuote(_)
+            | TagEnd::CodeBlock
+            | TagEnd::HtmlBlock
+            | TagEnd::List(_)
+            | TagEnd::Table
+            | TagEnd::DefinitionList
+            | TagEnd::DefinitionListDefinition,
+         ) => self.separate(Separator::Block),
+
+         _ => {}
+      }
+   }
+
+   fn separate(&mut self, separator: Separator) {
+      // Code blocks end with a newline, which the separator replaces.
+      if separator > Separator::Space {
+         let trimmed = self.text.trim_end().len();
+         self.text.truncate(trimmed);
+      }
+
+      self.separator = self.separator.max(Some(separator));
+   }
+
+   pub(super) fn finish(self) -> String {
+      self.text
+   }
+}
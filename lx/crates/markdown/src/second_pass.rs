@@ -8,18 +8,21 @@ use thiserror::Error;
 
 use super::FootnoteDefinitions;
 use super::first_pass;
+use super::plain::Plain;
 
-/// The second pass through the events is responsible for three tasks:
+/// The second pass through the events is responsible for four tasks:
 ///
 /// 1. Applying syntax highlighting.
 /// 2. Properly emitting footnotes.
 /// 3. Performing any template-language-type rewriting of text nodes.
+/// 4. Collecting the plain text of the content, along with the events for its HTML.
 struct State<'e, 's> {
    footnote_definitions: FootnoteDefinitions<'e>,
    syntax_set: &'s SyntaxSet,
    code_block: Option<CodeBlock<'e, 's>>,
    events: Vec<pulldown_cmark::Event<'e>>,
    emitted_definitions: Vec<(CowStr<'e>, Vec<pulldown_cmark::Event<'e>>)>,
+   plain: Plain,
 }
 
 #[derive(Error, Debug)]
@@ -53,13 +56,14 @@ pub(super) fn second_pass<'e>(
    syntax_set: &SyntaxSet,
    events: Vec<first_pass::Event<'e>>,
    rewrite: impl Fn(&str) -> Result<String, Box<dyn error::Error + Send + Sync>>,
-) -> Result<impl Iterator<Item = pulldown_cmark::Event<'e>>, Error> {
+) -> Result<(impl Iterator<Item = pulldown_cmark::Event<'e>>, String), Error> {
    let mut state = State {
       footnote_definitions,
       syntax_set,
       code_block: None,
       events: vec![],
       emitted_definitions: vec![],
+      plain: Plain::default(),
    };
 
    for event in events {
@@ -70,7 +74,9 @@ pub(super) fn second_pass<'e>(
       }
    }
 
-   Ok(state.into_iter())
+   // Footnote definitions are only emitted as HTML, so the plain text is complete.
+   let plain = std::mem::take(&mut state.plain).finish();
+   Ok((state.into_iter(), plain))
 }
 
 impl<'e> State<'e, '_> {
@@ -90,6 +96,7 @@ impl<'e> State<'e, '_> {
                match self.code_block {
                   Some(ref mut code_block) => {
                      code_block.highlight(&text)?;
+                     self.plain.text(&text);
                      Ok(None)
                   }
                   None => {
@@ -98,6 +105,7 @@ impl<'e> State<'e, '_> {
                            source,
                            original: text.to_string(),
                         })?;
+                     self.plain.text(&rewritten);
                      self.events.push(Html(rewritten.into()));
                      Ok(None)
                   }
@@ -111,6 +119,7 @@ impl<'e> State<'e, '_> {
 
             End(TagEnd::CodeBlock) => match self.code_block.take() {
                Some(code_block) => {
+                  self.plain.event(&End(TagEnd::CodeBlock));
                   self.events.append(&mut code_block.end());
                   Ok(None)
                }
@@ -118,6 +127,7 @@ impl<'e> State<'e, '_> {
             },
 
             DisplayMath(content) => {
+               self.plain.event(&DisplayMath(content.clone()));
                let math = latex2mathml::latex_to_mathml(
                   content.as_ref(),
                   latex2mathml::DisplayStyle::Block,
@@ -127,6 +137,7 @@ impl<'e> State<'e, '_> {
             }
 
             InlineMath(content) => {
+               self.plain.event(&InlineMath(content.clone()));
                let math = latex2mathml::latex_to_mathml(
                   content.as_ref(),
                   latex2mathml::DisplayStyle::Inline,
@@ -143,6 +154,7 @@ impl<'e> State<'e, '_> {
 
             // Everything else can just be emitted exactly as is.
             other => {
+               self.plain.event(&other);
                self.events.push(other.clone());
                Ok(None)
             }
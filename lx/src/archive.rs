@@ -1,7 +1,7 @@
+use camino::Utf8PathBuf;
 use chrono::{Datelike, Month};
 use indexmap::{IndexMap, IndexSet};
-use minijinja::Environment;
-use serde::Serialize;
+use serde::{Deserialize, Serialize, Serializer};
 use thiserror::Error;
 
 use crate::{
@@ -11,20 +11,34 @@ use crate::{
 
 /// A data structure that maps each post to Y -> M -> D -> posts, preserving the order of
 /// the posts.
-#[derive(Debug, Serialize)]
+///
+/// For templates, it serializes as a list of [`YearView`]s, so that each year, month,
+/// and day has a human-readable name and a path to its own archive page.
+#[derive(Debug)]
 pub struct Archive<'p>(IndexMap<Year, MonthMap<'p>>);
 
 impl<'e> Archive<'e> {
-   /// Reference all pages in an unordered fashion.
+   /// Reference all posts among the items, sorted by date in the given order.
    pub fn new(
       items: impl IntoIterator<Item = &'e Item<'e>>,
+      order: Order,
    ) -> Result<Archive<'e>, Error> {
       let mut year_map = IndexMap::<Year, MonthMap<'e>>::new();
 
-      let posts = items.into_iter().filter_map(|item| match item {
-         Item::Page(_) => None,
-         Item::Post(post) => Some(post),
-      });
+      let mut posts = items
+         .into_iter()
+         .filter_map(|item| match item {
+            Item::Page(_) => None,
+            Item::Post(post) => Some(post),
+         })
+         .collect::<Vec<_>>();
+
+      // Since the maps preserve insertion order, sorting the posts up front orders the
+      // years, months, and days as well.
+      match order {
+         Order::OldFirst => posts.sort(),
+         Order::NewFirst => posts.sort_by(|a, b| b.cmp(a)),
+      }
 
       for post in posts {
          let year = Year::from(post.date.year_ce().1);
@@ -42,18 +56,133 @@ impl<'e> Archive<'e> {
 
       Ok(Archive(year_map))
    }
+
+   /// The views for each year in the archive, in order.
+   pub fn years(&self) -> Vec<YearView<'_>> {
+      self
+         .0
+         .iter()
+         .map(|(year, months)| YearView {
+            name: year.raw.to_string(),
+            number: year.raw,
+            path: year.path(),
+            months: months
+               .iter()
+               .map(|(month, days)| MonthView {
+                  name: month.name(),
+                  number: month.number_from_month(),
+                  path: year
+                     .path()
+                     .join(format!("{:02}", month.number_from_month())),
+                  days: days
+                     .iter()
+                     .map(|(day, post_links)| DayView {
+                        name: day.raw.to_string(),
+                        number: day.raw,
+                        path: year
+                           .path()
+                           .join(format!("{:02}", month.number_from_month()))
+                           .join(format!("{:02}", day.raw)),
+                        items: post_links.iter().collect(),
+                     })
+                     .collect(),
+               })
+               .collect(),
+         })
+         .collect()
+   }
+}
+
+impl Serialize for Archive<'_> {
+   fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+      self.years().serialize(serializer)
+   }
 }
 
 impl<'e> Component for Archive<'e> {
    const VIEW_NAME: &'static str = "archive";
 }
 
-#[allow(dead_code)]
+/// How to build the site's archives, as set by `archive` in the site config.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Options {
+   /// Which posts come first.
+   #[serde(default)]
+   pub order: Order,
+
+   /// Whether to render an archive page for each day, as well as for each year and
+   /// month.
+   #[serde(default)]
+   pub days: bool,
+}
+
+/// Which posts come first in an archive. Configurable per site with `archive.order`,
+/// as either `new-first` (the default) or `old-first`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Order {
    OldFirst,
+   #[default]
    NewFirst,
 }
 
+impl Order {
+   /// Sort items by date in this order. Pages have no date, so they always come after
+   /// all posts.
+   pub fn sort(self, items: &mut [&Item]) {
+      items.sort_by(|a, b| match (a.date(), b.date()) {
+         (Some(a), Some(b)) => match self {
+            Order::OldFirst => a.cmp(&b),
+            Order::NewFirst => b.cmp(&a),
+         },
+         (Some(_), None) => std::cmp::Ordering::Less,
+         (None, Some(_)) => std::cmp::Ordering::Greater,
+         (None, None) => std::cmp::Ordering::Equal,
+      });
+   }
+}
+
+/// A year in the archive, e.g. `2024`, with its archive page at `/2024/`.
+#[derive(Debug, Serialize)]
+pub struct YearView<'a> {
+   pub name: String,
+   pub number: u32,
+   pub path: Utf8PathBuf,
+   pub months: Vec<MonthView<'a>>,
+}
+
+impl<'a> YearView<'a> {
+   /// Every post in the year, in order.
+   pub fn links(&self) -> impl Iterator<Item = &'a PostLink<'a>> + '_ {
+      self.months.iter().flat_map(MonthView::links)
+   }
+}
+
+/// A month in the archive, e.g. `January`, with its archive page at `/2024/01/`.
+#[derive(Debug, Serialize)]
+pub struct MonthView<'a> {
+   pub name: &'static str,
+   pub number: u32,
+   pub path: Utf8PathBuf,
+   pub days: Vec<DayView<'a>>,
+}
+
+impl<'a> MonthView<'a> {
+   /// Every post in the month, in order.
+   pub fn links(&self) -> impl Iterator<Item = &'a PostLink<'a>> + '_ {
+      self.days.iter().flat_map(|day| day.items.iter().copied())
+   }
+}
+
+/// A day in the archive, with its archive page at `/2024/01/31/`.
+#[derive(Debug, Serialize)]
+pub struct DayView<'a> {
+   pub name: String,
+   pub number: u8,
+   pub path: Utf8PathBuf,
+   pub items: Vec<&'a PostLink<'a>>,
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
    #[error("nonsense month value: '{raw}")]
@@ -81,6 +210,12 @@ impl From<u32> for Year {
    }
 }
 
+impl Year {
+   fn path(&self) -> Utf8PathBuf {
+      Utf8PathBuf::from(self.raw.to_string())
+   }
+}
+
 type MonthMap<'p> = IndexMap<Month, DayMap<'p>>;
 
 type DayMap<'p> = IndexMap<Day, IndexSet<PostLink<'p>>>;
@@ -108,3 +243,23 @@ impl TryFrom<u32> for Day {
 pub struct BadDay {
    raw: u32,
 }
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn options_default_to_new_first_without_days() {
+      let options: Options = serde_yaml::from_str("{}").unwrap();
+      assert_eq!(options.order, Order::NewFirst);
+      assert!(!options.days);
+   }
+
+   #[test]
+   fn options_from_config() {
+      let options: Options =
+         serde_yaml::from_str("order: old-first\ndays: true").unwrap();
+      assert_eq!(options.order, Order::OldFirst);
+      assert!(options.days);
+   }
+}
@@ -1,59 +1,263 @@
-use std::{error, fmt, fs, io};
+mod check;
+mod failure;
+mod incremental;
+mod input;
+mod output;
+
+pub use check::check;
+pub use failure::Failure;
+pub use input::{Changed, Input, InputDirs};
+pub use output::{Memory, changes};
+
+use std::{collections::HashMap, error, fmt, fs, io};
 
 use camino::{Utf8Path, Utf8PathBuf};
+use chrono::Local;
 use lazy_static::lazy_static;
-use log::{debug, error, trace};
+use log::{debug, error, trace, warn};
+use minijinja::Value;
 use rayon::{iter::Either, prelude::*};
+use serde::Serialize;
 use thiserror::Error;
 
 use lx_md::Markdown;
 
 use crate::{
-   archive::Archive,
+   archive::{self, Archive, DayView, MonthView, YearView},
+   build::{
+      check::OnError,
+      incremental::Plan,
+      output::{Files, Sources, Writer},
+   },
+   cache::{self, Cache},
    canonicalized::Canonicalized,
    data::{
       config::{self, Config},
-      item::cascade::{Cascade, CascadeLoadError},
+      item::{
+         BookView,
+         cascade::{Cascade, CascadeLoadError},
+      },
    },
    error::write_to_fmt,
-   page::{self, Item, Source},
-   style, templates,
+   feed,
+   library::{self, Library},
+   links,
+   page::{self, Item, PostLink, Source},
+   pagination::{self, Pagination, paginate},
+   publication, series, style,
+   taxonomy::{self, Taxonomy, Term, TermSummary, Terms},
+   templates::{self, site::Site},
 };
 
-pub fn build_in(directory: Canonicalized) -> Result<(), Error> {
+pub fn build_in(
+   directory: Canonicalized,
+   cache: &Cache,
+   publication: publication::Options,
+) -> Result<(), Error> {
    let config = config_for(&directory)?;
-   build(&directory, &config, &Markdown::new(None), Mode::Build)
+   build(
+      &directory,
+      &config,
+      &Markdown::new(None),
+      cache,
+      Mode::Build(publication),
+   )
+}
+
+pub fn config_path(source_dir: &Canonicalized) -> Utf8PathBuf {
+   source_dir.as_ref().join("config.lx.yaml")
 }
 
 pub fn config_for(source_dir: &Canonicalized) -> Result<Config, Error> {
-   let config_path = source_dir.as_ref().join("config.lx.yaml");
+   let config_path = config_path(source_dir);
    debug!("source path: {source_dir}");
    debug!("config path: {config_path}");
    let config = Config::from_file(&config_path)?;
    Ok(config)
 }
 
-pub enum Mode {
-   Build,
-   Serve,
+pub enum Mode<'m> {
+   /// Write the site to the output directory, with only the items the options include.
+   Build(publication::Options),
+
+   /// Keep the site in memory, to be served from there, with every item in it whether
+   /// it is published or not.
+   Serve(&'m Memory),
+}
+
+/// One run of [`render_site`]: what it writes, which items it includes, and what it does
+/// with any errors along the way.
+struct Pass<'p> {
+   plan: Plan,
+   publication: publication::Options,
+   on_error: OnError<'p>,
 }
 
-// TODO: further split this apart.
 pub fn build(
    directory: &Canonicalized,
    config: &Config,
    md: &Markdown,
+   cache: &Cache,
    mode: Mode,
 ) -> Result<(), Error> {
    debug!("Building in {directory}");
 
-   clear_output_dir(config, mode)?;
+   let (out, publication) = match mode {
+      Mode::Build(publication) => {
+         clear_output_dir(config)?;
+         (Writer::disk(&config.output)?, publication)
+      }
+      Mode::Serve(memory) => (
+         Writer::memory(&config.output, memory, Files::new(), Sources::new()),
+         publication::Options::everything(Local::now().fixed_offset()),
+      ),
+   };
+
+   let pass = Pass {
+      plan: Plan::everything(),
+      publication,
+      on_error: OnError::Stop,
+   };
+   render_site(directory, config, md, cache, &out, pass)?;
+   out.finish();
+
+   if let Mode::Build(_) = mode {
+      check_links(directory, config)?;
+   }
+
+   Ok(())
+}
+
+/// Check the links in the output directory: broken internal links fail the build in
+/// strict mode and are only warned about otherwise.
+fn check_links(directory: &Canonicalized, config: &Config) -> Result<(), Error> {
+   debug!("Checking links in {}", config.output);
+
+   let mut checker = links::Checker::new(&config.url);
+   for path in resolved_paths_for(&format!("{}/**/*", config.output))? {
+      let relative = path
+         .strip_prefix(&config.output)
+         .map_err(|_| Error::StripPrefix {
+            prefix: config.output.clone(),
+            path: path.clone(),
+         })?
+         .to_owned();
+
+      if links::is_page(&relative) {
+         let html = fs::read_to_string(&path)
+            .map_err(|source| Error::ReadOutput { path, source })?;
+         checker.add_page(relative, &html);
+      } else {
+         checker.add_file(relative);
+      }
+   }
+
+   let report = checker.check();
+
+   if let Some(report_path) = &config.links.external_report {
+      let path = directory.as_ref().join(report_path);
+      let json = serde_json::to_string_pretty(&report.external)
+         .expect("paths and URLs always serialize");
+      fs::write(&path, json).map_err(|source| Error::WriteFile { path, source })?;
+   }
+
+   if report.broken.is_empty() {
+      Ok(())
+   } else if config.links.strict {
+      Err(links::Error {
+         broken: report.broken,
+      })?
+   } else {
+      for broken in &report.broken {
+         warn!("broken link in {broken}");
+      }
+      Ok(())
+   }
+}
+
+/// Rebuild after a change to the given paths, writing only the output they affect over
+/// the output of the previous build, unless the changes require a full build: because
+/// of what changed, because there is no previous build to write over, or because a
+/// changed item moved to a different output path, which other pages may link to.
+pub fn rebuild(
+   directory: &Canonicalized,
+   config: &Config,
+   md: &Markdown,
+   cache: &Cache,
+   memory: &Memory,
+   changed: &[Changed],
+) -> Result<(), Error> {
+   let plan = Plan::for_changes(changed);
+   if plan.is_everything() {
+      return build(directory, config, md, cache, Mode::Serve(memory));
+   }
+
+   // E.g. if the first build failed, there is nothing to write over yet.
+   let Some(previous) = memory.sources() else {
+      return build(directory, config, md, cache, Mode::Serve(memory));
+   };
+
+   debug!(
+      "Rebuilding in {directory} for {} changed paths",
+      changed.len()
+   );
+
+   // Drop whatever the changed sources wrote last time, so that none of it survives if
+   // they do not write it again.
+   let changed_content = plan
+      .changed_content()
+      .map(Utf8Path::to_owned)
+      .collect::<Vec<_>>();
+   let mut files = memory.snapshot().as_ref().clone();
+   let mut sources = previous.as_ref().clone();
+   for source in &changed_content {
+      for path in sources.remove(source).unwrap_or_default() {
+         files.remove(&path);
+      }
+   }
+
+   let out = Writer::memory(&config.output, memory, files, sources);
+   let pass = Pass {
+      plan,
+      publication: publication::Options::everything(Local::now().fixed_offset()),
+      on_error: OnError::Stop,
+   };
+   render_site(directory, config, md, cache, &out, pass)?;
+
+   let moved = changed_content.iter().any(|source| {
+      previous
+         .get(source)
+         .is_some_and(|before| *before != out.outputs_of(source))
+   });
+   if moved {
+      debug!("changed items moved, so rebuilding everything");
+      return build(directory, config, md, cache, Mode::Serve(memory));
+   }
+
+   out.finish();
+   Ok(())
+}
+
+// TODO: further split this apart.
+fn render_site(
+   directory: &Canonicalized,
+   config: &Config,
+   md: &Markdown,
+   cache: &Cache,
+   out: &Writer,
+   pass: Pass,
+) -> Result<(), Error> {
+   let Pass {
+      plan,
+      publication,
+      on_error,
+   } = pass;
 
    let input_dir = directory.as_ref();
    let site_files = SiteFiles::in_dir(input_dir)?;
    trace!("Site files: {site_files}");
 
-   let shared_dir = input_dir.parent().map(|parent| parent.join("_shared"));
+   let shared_dir = InputDirs::for_site(directory).shared;
    let mut shared_files = shared_dir
       .as_ref()
       .map(|dir| SharedFiles::in_dir(dir))
@@ -74,7 +278,7 @@ pub fn build(
 
    trace!("all templates: {all_templates:?}");
 
-   let jinja_env = templates::load(all_templates, |path| {
+   let mut jinja_env = templates::load(all_templates, |path| {
       let site_ui_dir = input_dir.join(&*UI_DIR);
       if path.starts_with(&site_ui_dir) {
          Ok(path.strip_prefix(&site_ui_dir).unwrap())
@@ -94,63 +298,158 @@ pub fn build(
       }
    })?;
 
-   fs::create_dir_all(&config.output).map_err(|source| Error::CreateDir {
-      path: config.output.clone(),
-      source,
-   })?;
+   let plan = plan.resolve(&jinja_env);
 
-   let sources = load_sources(&site_files.content)?;
+   let (sources, errors) = read_sources(&site_files.content);
+   if !errors.is_empty() {
+      on_error.handle(Error::Content(errors))?;
+   }
 
    debug!("loaded {count} pages", count = sources.len());
 
-   let cascade =
-      Cascade::new(&site_files.data).map_err(|source| Error::Cascade { source })?;
+   let (cascade, errors) = Cascade::load(&site_files.data);
+   for source in errors {
+      on_error.handle(Error::Cascade { source })?;
+   }
+
+   let inputs =
+      cache::Inputs::new(config, &site_files.data, md.syntax_set(), &jinja_env)?;
+   let entries = cache.entries(inputs);
 
+   // Items whose rendered form is in the cache skip both preparing and rendering.
    let (errors, prepared_pages): (Vec<_>, Vec<_>) = sources
       .par_iter()
       // NOTE: this is where I will want to add handling for `<page>.lx.yaml` files; when
       // I add support for that, this will not be a filter but will do different things in
       // the map call depending on what kind of file it is.
       .filter(|source| source.path.extension().is_some_and(|ext| ext == "md"))
-      .map(|source| {
-         page::prepare(md, source, &cascade)
-            .map(|prepared| (prepared, source))
-            .map_err(|e| (source.path.clone(), e))
+      .map(|source| match entries.get(source) {
+         Some(rendered) => Ok((Either::Left(rendered), source)),
+         None => page::prepare(md, source, &cascade, &config.taxonomies)
+            .map(|prepared| (Either::Right(prepared), source))
+            .map_err(|e| (source.path.clone(), e)),
       })
       .partition_map(Either::from);
 
    if !errors.is_empty() {
-      return Err(Error::preparing_page(errors));
+      on_error.handle(Error::preparing_page(errors))?;
    }
 
-   debug!("prepared {count} pages", count = prepared_pages.len());
+   debug!(
+      "prepared {count} pages ({cached} from cache)",
+      count = prepared_pages.len(),
+      cached = prepared_pages
+         .iter()
+         .filter(|(prepared, _)| prepared.is_left())
+         .count()
+   );
 
    let content_dir = input_dir.join("content");
 
    let (errors, items): (Vec<_>, Vec<_>) = prepared_pages
       .into_par_iter()
       .map(|(prepared, source)| {
-         // TODO: once the taxonomies exist, pass them here.
-         prepared
-            .render(md, |text, metadata| {
-               let after_jinja = jinja_env
-                  .render_str(text, metadata)
-                  .map_err(|source| Error::rewrite(source, text))?;
-               // TODO: smarten the typography!
-               Ok(after_jinja)
-            })
+         let rendered = match prepared {
+            Either::Left(cached) => Ok(cached),
+            // TODO: once the taxonomies exist, pass them here.
+            Either::Right(prepared) => prepared
+               .render(md, |text, metadata| {
+                  let after_jinja = jinja_env
+                     .render_str(text, metadata)
+                     .map_err(|source| Error::rewrite(source, text))?;
+                  // TODO: smarten the typography!
+                  Ok(after_jinja)
+               })
+               .inspect(|rendered| entries.put(source, rendered)),
+         };
+
+         rendered
             .and_then(|rendered| Item::from_rendered(rendered, source, &content_dir))
             .map_err(|e| (source.path.clone(), e))
       })
       .partition_map(Either::from);
 
    if !errors.is_empty() {
-      return Err(Error::rendering_page(errors));
+      on_error.handle(Error::rendering_page(errors))?;
+   }
+
+   entries.prune();
+
+   // Unpublished parts of a series are still parts of it, so check the series before
+   // leaving them out.
+   series::check(&items)?;
+
+   let items = items
+      .into_iter()
+      .filter(|item| publication.includes(item))
+      .collect::<Vec<_>>();
+
+   if let Err(error) = check_output_paths(&items) {
+      on_error.handle(error)?;
+   }
+
+   let archive = Archive::new(&items, config.archive.order)?;
+   let series = series::Index::new(&items);
+
+   jinja_env.add_global(
+      "site",
+      Value::from_object(Site::new(&items, &archive, &content_dir)),
+   );
+
+   if plan.writes_feeds() {
+      for format in [feed::Format::Json, feed::Format::Atom] {
+         let written = feed::feeds_for(format, config, &items, &cascade, &content_dir)
+            .map_err(Error::from)
+            .and_then(|feeds| {
+               for feed in feeds
+                  .into_iter()
+                  .flat_map(|feed| feed.paginated(config.pagination))
+               {
+                  let path = config.output.join(feed.path());
+                  trace!("writing {format:?} feed to {path}");
+                  out.emit(&path, feed.render()?)?;
+               }
+               Ok(())
+            });
+
+         if let Err(error) = written {
+            on_error.handle(error)?;
+         }
+      }
+   }
+
+   if let Err(error) = render_archives(&jinja_env, out, &archive, config, &plan) {
+      on_error.handle(error)?;
+   }
+   if let Err(error) = render_taxonomies(&jinja_env, out, &items, config, &plan) {
+      on_error.handle(error)?;
    }
+   if let Err(error) = render_library(&jinja_env, out, &items, config, &plan) {
+      on_error.handle(error)?;
+   }
+
+   let templated = load_sources(&site_files.templated)?;
+   for source in &templated {
+      if !plan.writes_templated(&source.path, &source.contents) {
+         continue;
+      }
 
-   // TODO: build taxonomies.
-   // TODO: Identify the taxonomical system I want to use for the site(s)!
-   let _archive = Archive::new(&items);
+      let rendered =
+         templated_output_path(&source.path, &content_dir).and_then(|relative_path| {
+            let path = config.output.join(relative_path);
+            trace!("writing templated file {} to {path}", source.path);
+
+            let mut buf = Vec::new();
+            templates::render_templated(
+               &jinja_env, source, &items, &archive, config, &mut buf,
+            )?;
+            out.emit(&path, &buf)
+         });
+
+      if let Err(error) = rendered {
+         on_error.handle(error)?;
+      }
+   }
 
    // TODO: this and the below are identical, except for the directory from which they
    // come. This is suggestive: maybe extract into a function for handling both, and
@@ -160,6 +459,10 @@ pub fn build(
    if let Some(shared) = shared_files.as_mut() {
       debug!("Copying {} shared static files", shared.static_files.len());
       for static_file in shared.static_files.iter() {
+         if !plan.copies_static(static_file) {
+            continue;
+         }
+
          let relative_path = static_file
             .strip_prefix(shared_dir.as_ref().unwrap().join("_static"))
             .map_err(|_| Error::StripPrefix {
@@ -167,12 +470,16 @@ pub fn build(
                path: static_file.clone(),
             })?;
          let path = config.output.join(relative_path);
-         copy(static_file, &path)?;
+         out.copy(static_file, &path)?;
       }
    }
 
    debug!("Copying {} static files", site_files.static_files.len());
    for static_file in site_files.static_files.iter() {
+      if !plan.copies_static(static_file) {
+         continue;
+      }
+
       let relative_path = static_file
          .strip_prefix(input_dir.join("_static"))
          .map_err(|_| Error::StripPrefix {
@@ -180,33 +487,26 @@ pub fn build(
             path: static_file.clone(),
          })?;
       let path = config.output.join(relative_path);
-      copy(static_file, &path)?;
+      out.copy(static_file, &path)?;
    }
 
    // TODO: this can and probably should use async?
-   for item in items {
-      let relative_path = item.path().as_ref().join("index.html");
-      let path = config.output.join(relative_path);
-
-      trace!("writing page {} to {}", item.title(), path);
-      let containing_dir = path
-         .parent()
-         .unwrap_or_else(|| panic!("{path} should have a containing dir!"));
-
-      fs::create_dir_all(containing_dir).map_err(|e| Error::CreateOutputDirectory {
-         path: containing_dir.to_owned(),
-         source: e,
-      })?;
-
-      let mut buf = Vec::new();
-      templates::render(&jinja_env, &item, config, &mut buf)?;
+   for item in &items {
+      if !plan.writes_item(item, &items) {
+         continue;
+      }
 
-      emit(&path, &buf)?;
+      if let Err(error) =
+         write_item(&jinja_env, out, item, &items, &series, config, &publication)
+      {
+         on_error.handle(error)?;
+      }
    }
 
    for css_file in site_files
       .styles
       .into_iter()
+      .filter(|_| plan.writes_styles())
       // only build the “root” files
       .filter(|path| {
          !path
@@ -226,15 +526,436 @@ pub fn build(
             })?;
 
       let path = config.output.join(relative_path).with_extension("css");
-      emit(&path, &converted)?;
+      out.emit(&path, &converted)?;
+   }
+
+   Ok(())
+}
+
+/// Write the page for an item, or for a paginated item, each of its pages.
+fn write_item(
+   jinja_env: &minijinja::Environment,
+   out: &Writer,
+   item: &Item,
+   items: &[Item],
+   series: &series::Index,
+   config: &Config,
+   publication: &publication::Options,
+) -> Result<(), Error> {
+   let unpublished = publication.unpublished(item);
+
+   if !item.data().paginate {
+      let path = config.output.join(item.path().as_ref()).join("index.html");
+      trace!("writing page {} to {}", item.source().path, path);
+
+      let mut buf = Vec::new();
+      let series = series.context_for(item);
+      templates::render(jinja_env, item, series, None, unpublished, config, &mut buf)?;
+      return out.emit_for(&item.source().path, &path, &buf);
+   }
+
+   let mut listed = posts_alongside(item, items);
+   config.archive.order.sort(&mut listed);
+
+   for page in paginate(&listed, config.pagination, item.path().as_ref()) {
+      let path = config.output.join(&page.path).join("index.html");
+      trace!("writing page {} to {}", item.source().path, path);
+
+      let mut buf = Vec::new();
+      let series = series.context_for(item);
+      templates::render(
+         jinja_env,
+         item,
+         series,
+         Some(&page),
+         unpublished,
+         config,
+         &mut buf,
+      )?;
+      out.emit_for(&item.source().path, &path, &buf)?;
+   }
+
+   Ok(())
+}
+
+/// The posts in the same directory as the given item, or any directory below it, which is
+/// what a paginated item lists.
+fn posts_alongside<'a>(item: &Item, items: &'a [Item<'a>]) -> Vec<&'a Item<'a>> {
+   let dir = item.source().path.parent().unwrap_or(Utf8Path::new(""));
+   items
+      .iter()
+      .filter(|other| matches!(other, Item::Post(_)))
+      .filter(|other| other.source().path.starts_with(dir))
+      .collect()
+}
+
+/// Render the full archive at `/archive/` with the `archive.jinja` layout, along with a
+/// page for each year (`year.jinja`), month (`month.jinja`), and, if the site config
+/// asks for them, day (`day.jinja`). Sites without any posts get no archive pages.
+///
+/// Every archive page gets the posts it covers as `pagination`, so that long years and
+/// months can be split into pages like any other list. The full archive page instead
+/// gets the whole `archive`, already grouped by year, month, and day, so it is never
+/// split: its one page has every post.
+fn render_archives(
+   jinja_env: &minijinja::Environment,
+   out: &Writer,
+   archive: &Archive,
+   config: &Config,
+   plan: &Plan,
+) -> Result<(), Error> {
+   #[derive(Serialize)]
+   struct Context<'a> {
+      config: &'a Config,
+      path: &'a Utf8Path,
+      #[serde(skip_serializing_if = "Option::is_none")]
+      archive: Option<&'a Archive<'a>>,
+      #[serde(skip_serializing_if = "Option::is_none")]
+      year: Option<&'a YearView<'a>>,
+      #[serde(skip_serializing_if = "Option::is_none")]
+      month: Option<&'a MonthView<'a>>,
+      #[serde(skip_serializing_if = "Option::is_none")]
+      day: Option<&'a DayView<'a>>,
+      pagination: &'a Pagination<'a, &'a PostLink<'a>>,
+   }
+
+   let render = |layout: &str,
+                 base: &Utf8Path,
+                 links: &[&PostLink],
+                 year: Option<&YearView>,
+                 month: Option<&MonthView>,
+                 day: Option<&DayView>|
+    -> Result<(), Error> {
+      if !plan.writes_aggregate(layout) {
+         return Ok(());
+      }
+
+      let options = match year {
+         Some(_) => config.pagination,
+         None => pagination::Options::default(),
+      };
+
+      for page in paginate(links, options, base) {
+         let path = config.output.join(&page.path).join("index.html");
+         trace!("writing archive page to {path}");
+
+         let context = Context {
+            config,
+            path: &page.path,
+            archive: year.is_none().then_some(archive),
+            year,
+            month,
+            day,
+            pagination: &page,
+         };
+
+         let mut buf = Vec::new();
+         templates::render_view(jinja_env, layout, context, &path, &mut buf)?;
+         out.emit(&path, &buf)?;
+      }
+
+      Ok(())
+   };
+
+   let years = archive.years();
+   if years.is_empty() {
+      return Ok(());
+   }
+
+   let all = years.iter().flat_map(YearView::links).collect::<Vec<_>>();
+   render(
+      "archive.jinja",
+      Utf8Path::new("archive"),
+      &all,
+      None,
+      None,
+      None,
+   )?;
+
+   for year in &years {
+      let links = year.links().collect::<Vec<_>>();
+      render("year.jinja", &year.path, &links, Some(year), None, None)?;
+
+      for month in &year.months {
+         let links = month.links().collect::<Vec<_>>();
+         render(
+            "month.jinja",
+            &month.path,
+            &links,
+            Some(year),
+            Some(month),
+            None,
+         )?;
+
+         if !config.archive.days {
+            continue;
+         }
+
+         for day in &month.days {
+            let links = day.items.clone();
+            render(
+               "day.jinja",
+               &day.path,
+               &links,
+               Some(year),
+               Some(month),
+               Some(day),
+            )?;
+         }
+      }
+   }
+
+   Ok(())
+}
+
+/// Render a page for every term in each of the site's taxonomies, including the
+/// built-in tags taxonomy, along with an index of all tags.
+fn render_taxonomies(
+   jinja_env: &minijinja::Environment,
+   out: &Writer,
+   items: &[Item],
+   config: &Config,
+   plan: &Plan,
+) -> Result<(), Error> {
+   for taxonomy in &config.taxonomies {
+      if plan.writes_aggregate(taxonomy.layout()) {
+         render_terms(
+            jinja_env,
+            out,
+            config,
+            taxonomy,
+            &Terms::collect(taxonomy, items)?,
+         )?;
+      }
+   }
+
+   let tags = Taxonomy::tags();
+   let tag_terms = Terms::tags(items)?;
+   if plan.writes_aggregate(tags.layout()) {
+      render_terms(jinja_env, out, config, &tags, &tag_terms)?;
+   }
+
+   if plan.writes_aggregate("tags.jinja") {
+      render_tag_index(jinja_env, out, config, &tags, &tag_terms)?;
+   }
+
+   Ok(())
+}
+
+fn render_terms(
+   jinja_env: &minijinja::Environment,
+   out: &Writer,
+   config: &Config,
+   taxonomy: &Taxonomy,
+   terms: &Terms,
+) -> Result<(), Error> {
+   #[derive(Serialize)]
+   struct Context<'a> {
+      config: &'a Config,
+      taxonomy: &'a Taxonomy,
+      term: &'a Term,
+      name: &'a str,
+      path: &'a Utf8Path,
+      items: &'a [&'a Item<'a>],
+      archive: Archive<'a>,
+      pagination: &'a Pagination<'a, &'a Item<'a>>,
+   }
+
+   debug!(
+      "rendering {count} terms for taxonomy '{name}'",
+      count = terms.items.len(),
+      name = taxonomy.name
+   );
+
+   for (term, term_items) in &terms.items {
+      let mut term_items = term_items.clone();
+      config.archive.order.sort(&mut term_items);
+
+      let relative_dir = taxonomy.path().join(term.path());
+      for page in paginate(&term_items, config.pagination, &relative_dir) {
+         let path = config.output.join(&page.path).join("index.html");
+         trace!("writing term '{}' page to {path}", term.name());
+
+         let context = Context {
+            config,
+            taxonomy,
+            term,
+            name: term.name(),
+            path: &page.path,
+            items: page.items,
+            archive: Archive::new(page.items.iter().copied(), config.archive.order)?,
+            pagination: &page,
+         };
+
+         let mut buf = Vec::new();
+         templates::render_view(jinja_env, taxonomy.layout(), context, &path, &mut buf)?;
+         out.emit(&path, &buf)?;
+      }
+   }
+
+   Ok(())
+}
+
+/// Render `/tags/index.html` with the `tags.jinja` layout, listing every tag along with
+/// the number of posts which use it. Sites without any tags get no index.
+fn render_tag_index(
+   jinja_env: &minijinja::Environment,
+   out: &Writer,
+   config: &Config,
+   tags: &Taxonomy,
+   terms: &Terms,
+) -> Result<(), Error> {
+   #[derive(Serialize)]
+   struct Context<'a> {
+      config: &'a Config,
+      taxonomy: &'a Taxonomy,
+      path: &'a Utf8Path,
+      terms: Vec<TermSummary<'a>>,
+   }
+
+   if terms.items.is_empty() {
+      return Ok(());
+   }
+
+   let relative_dir = tags.path();
+   let path = config.output.join(&relative_dir).join("index.html");
+   trace!("writing tag index to {path}");
+
+   let context = Context {
+      config,
+      taxonomy: tags,
+      path: &relative_dir,
+      terms: terms.summaries(),
+   };
+
+   let mut buf = Vec::new();
+   templates::render_view(jinja_env, "tags.jinja", context, &path, &mut buf)?;
+   out.emit(&path, &buf)
+}
+
+/// Render a page for every book in the library with the `book.jinja` layout, and the
+/// library index at `/library/` with the `library.jinja` layout.
+fn render_library(
+   jinja_env: &minijinja::Environment,
+   out: &Writer,
+   items: &[Item],
+   config: &Config,
+   plan: &Plan,
+) -> Result<(), Error> {
+   #[derive(Serialize)]
+   struct BookContext<'a> {
+      config: &'a Config,
+      path: &'a Utf8Path,
+      items: Vec<&'a Item<'a>>,
+      #[serde(flatten)]
+      view: BookView<'a>,
+   }
+
+   #[derive(Serialize)]
+   struct IndexContext<'a> {
+      config: &'a Config,
+      path: &'a Utf8Path,
+      #[serde(flatten)]
+      index: library::Index<'a>,
+   }
+
+   let library = Library::new(items)?;
+   if library.is_empty() {
+      return Ok(());
+   }
+
+   for book in library
+      .books()
+      .filter(|_| plan.writes_aggregate("book.jinja"))
+   {
+      let relative_dir = library::path_for(book);
+      let path = config.output.join(&relative_dir).join("index.html");
+      trace!("writing book page to {path}");
+
+      let about =
+         items
+            .iter()
+            .filter(|item| {
+               item.data().book.as_ref().is_some_and(|b| {
+                  b.title.is_some() && library::path_for(b) == relative_dir
+               })
+            })
+            .collect::<Vec<_>>();
+
+      let context = BookContext {
+         config,
+         path: &relative_dir,
+         view: book.as_view(about.iter().copied(), config.archive.order)?,
+         items: about,
+      };
+
+      let mut buf = Vec::new();
+      templates::render_view(jinja_env, "book.jinja", context, &path, &mut buf)?;
+      out.emit(&path, &buf)?;
+   }
+
+   if !plan.writes_aggregate("library.jinja") {
+      return Ok(());
+   }
+
+   let relative_dir = Utf8PathBuf::from("library");
+   let path = config.output.join(&relative_dir).join("index.html");
+   trace!("writing library index to {path}");
+
+   let context = IndexContext {
+      config,
+      path: &relative_dir,
+      index: library.index(),
+   };
+
+   let mut buf = Vec::new();
+   templates::render_view(jinja_env, "library.jinja", context, &path, &mut buf)?;
+   out.emit(&path, &buf)
+}
+
+/// Two items with the same output path would be written to the same file, with one
+/// silently replacing the other, so that is an error instead.
+fn check_output_paths(items: &[Item]) -> Result<(), Error> {
+   let mut seen = HashMap::<&Utf8Path, &Utf8Path>::new();
+   for item in items {
+      let path = item.path().as_ref();
+      if let Some(first) = seen.insert(path, &item.source().path) {
+         return Err(Error::DuplicatePath {
+            path: path.to_owned(),
+            first: first.to_owned(),
+            second: item.source().path.clone(),
+         });
+      }
    }
 
    Ok(())
 }
 
-fn clear_output_dir(config: &Config, _mode: Mode) -> Result<(), Error> {
-   // TODO: only do this if in `Mode::Build`; in `Mode::Serve`, clear in-memory cache
-   //   instead.
+/// The output location for a templated file: `feed.lx.xml` becomes `feed.xml`, in the
+/// same directory relative to the content root.
+fn templated_output_path(
+   source: &Utf8Path,
+   content_dir: &Utf8Path,
+) -> Result<Utf8PathBuf, Error> {
+   let relative = source
+      .strip_prefix(content_dir)
+      .map_err(|_| Error::StripPrefix {
+         prefix: content_dir.to_owned(),
+         path: source.to_owned(),
+      })?;
+
+   let name = relative
+      .file_name()
+      .and_then(|name| name.split_once(".lx."))
+      .map(|(stem, ext)| format!("{stem}.{ext}"))
+      .ok_or_else(|| Error::TemplatedName {
+         path: source.to_owned(),
+      })?;
+
+   Ok(relative.with_file_name(name))
+}
+
+fn clear_output_dir(config: &Config) -> Result<(), Error> {
    debug!("Removing output directory {}", config.output);
    if let Err(io_err) = fs::remove_dir_all(&config.output)
       && io_err.kind() != io::ErrorKind::NotFound
@@ -248,34 +969,21 @@ fn clear_output_dir(config: &Config, _mode: Mode) -> Result<(), Error> {
    }
 }
 
-fn copy(from: &Utf8Path, to: &Utf8Path) -> Result<(), Error> {
-   let output_dir = to.parent().expect("must have a real parent");
-   fs::create_dir_all(output_dir).map_err(|source| Error::CreateOutputDirectory {
-      path: output_dir.to_owned(),
-      source,
-   })?;
-   fs::copy(from, to).map_err(|source| Error::CopyFile {
-      from: from.to_owned(),
-      to: to.to_owned(),
-      source,
-   })?;
-   Ok(())
-}
-
-fn emit(path: &Utf8Path, content: impl AsRef<[u8]>) -> Result<(), Error> {
-   let output_dir = path.parent().expect("must have a real parent");
-   fs::create_dir_all(output_dir).map_err(|source| Error::CreateOutputDirectory {
-      path: output_dir.to_owned(),
-      source,
-   })?;
-   fs::write(path, content).map_err(|source| Error::WriteFile {
-      path: path.to_owned(),
-      source,
-   })?;
-   Ok(())
+fn load_sources<S>(source_files: S) -> Result<Vec<Source>, Error>
+where
+   S: IntoIterator,
+   S::Item: AsRef<Utf8Path>,
+{
+   let (sources, errors) = read_sources(source_files);
+   if errors.is_empty() {
+      Ok(sources)
+   } else {
+      Err(Error::Content(errors))
+   }
 }
 
-fn load_sources<S>(source_files: S) -> Result<Vec<Source>, Error>
+/// Read every source which can be read, along with the errors for any which cannot.
+fn read_sources<S>(source_files: S) -> (Vec<Source>, Vec<ContentError>)
 where
    S: IntoIterator,
    S::Item: AsRef<Utf8Path>,
@@ -296,15 +1004,17 @@ where
       }
    }
 
-   if errors.is_empty() {
-      Ok(sources)
-   } else {
-      Err(Error::Content(errors))
-   }
+   (sources, errors)
 }
 
 #[derive(Error, Debug)]
 pub enum Error {
+   #[error(transparent)]
+   Cache {
+      #[from]
+      source: cache::Error,
+   },
+
    #[error(transparent)]
    LoadTemplates {
       #[from]
@@ -323,6 +1033,12 @@ pub enum Error {
       source: CascadeLoadError,
    },
 
+   #[error("could not build feed")]
+   Feed {
+      #[from]
+      source: feed::Error,
+   },
+
    #[error("could not load site config: {source}")]
    Config {
       #[from]
@@ -348,6 +1064,18 @@ pub enum Error {
       source: io::Error,
    },
 
+   #[error("could not read output file {path}")]
+   ReadOutput {
+      path: Utf8PathBuf,
+      source: io::Error,
+   },
+
+   #[error(transparent)]
+   Links {
+      #[from]
+      source: links::Error,
+   },
+
    #[error("could not write to {path}")]
    WriteFile {
       path: Utf8PathBuf,
@@ -378,6 +1106,40 @@ pub enum Error {
    #[error("invalid template path {path}")]
    TemplatePath { path: Utf8PathBuf },
 
+   #[error("both '{first}' and '{second}' would be written to '{path}'")]
+   DuplicatePath {
+      path: Utf8PathBuf,
+      first: Utf8PathBuf,
+      second: Utf8PathBuf,
+   },
+
+   #[error("templated file '{path}' is not named like `<name>.lx.<ext>`")]
+   TemplatedName { path: Utf8PathBuf },
+
+   #[error(transparent)]
+   Series {
+      #[from]
+      source: series::Error,
+   },
+
+   #[error("could not build taxonomy pages")]
+   Taxonomy {
+      #[from]
+      source: taxonomy::Error,
+   },
+
+   #[error("could not build library")]
+   Library {
+      #[from]
+      source: library::Error,
+   },
+
+   #[error("could not build archive")]
+   Archive {
+      #[from]
+      source: archive::Error,
+   },
+
    #[error("could not delete directory '{path}'")]
    RemoveDir {
       path: Utf8PathBuf,
@@ -423,6 +1185,32 @@ impl Error {
    }
 }
 
+impl Error {
+   /// Each of the problems which caused the build to fail. Errors for individual files
+   /// are reported separately, so each can be tied to its file.
+   pub fn failures(&self) -> Vec<Failure> {
+      match self {
+         Error::Page(PageError { errors, .. }) => errors
+            .iter()
+            .map(|(path, error)| Failure::new(Some(path.clone()), error))
+            .collect(),
+
+         Error::Content(errors) => errors
+            .iter()
+            .map(|error| Failure::new(Some(error.path.clone()), &error.source))
+            .collect(),
+
+         Error::Cascade {
+            source:
+               CascadeLoadError::OpenFile { file, .. }
+               | CascadeLoadError::ParseMetadata { file, .. },
+         } => vec![Failure::new(Some(file.clone()), self)],
+
+         other => vec![Failure::new(None, other)],
+      }
+   }
+}
+
 #[derive(Debug)]
 enum PageErrorKind {
    Prepare,
@@ -456,22 +1244,6 @@ impl fmt::Display for PageError {
    }
 }
 
-#[derive(Error, Debug)]
-pub struct RewriteErrors(Vec<(Utf8PathBuf, minijinja::Error)>);
-
-impl fmt::Display for RewriteErrors {
-   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-      let errors = &self.0;
-      writeln!(f, "could not rewrite {} pages", errors.len())?;
-      for (path, error) in errors {
-         writeln!(f, "{path}:\n\t{error}")?;
-         write_to_fmt(f, error)?;
-      }
-
-      Ok(())
-   }
-}
-
 #[derive(Error, Debug)]
 #[error("Could not load file {path}")]
 pub struct ContentError {
@@ -487,6 +1259,9 @@ struct SiteFiles {
    config: Utf8PathBuf,
    content: Vec<Utf8PathBuf>,
    data: Vec<Utf8PathBuf>,
+   /// Non-HTML outputs (`feed.lx.xml`, `robots.lx.txt`, etc.), each of which is itself
+   /// a template.
+   templated: Vec<Utf8PathBuf>,
    templates: Vec<Utf8PathBuf>,
    static_files: Vec<Utf8PathBuf>,
    styles: Vec<Utf8PathBuf>,
@@ -498,15 +1273,20 @@ impl SiteFiles {
       trace!("content_dir: {content_dir}");
 
       let data = resolved_paths_for(&format!("{content_dir}/**/_data.lx.yaml"))?;
-      let content = resolved_paths_for(&format!("{content_dir}/**/*.md"))?
+      let templated = resolved_paths_for(&format!("{content_dir}/**/*.lx.*"))?
          .into_iter()
          .filter(|p| !data.contains(p))
+         .collect::<Vec<_>>();
+      let content = resolved_paths_for(&format!("{content_dir}/**/*.md"))?
+         .into_iter()
+         .filter(|p| !data.contains(p) && !templated.contains(p))
          .collect();
 
       let site_files = SiteFiles {
          config: in_dir.join("config.lx.yaml"),
          content,
          data,
+         templated,
          templates: resolved_paths_for(&format!("{in_dir}/{}/**/*.jinja", *UI_DIR))?,
          static_files: resolved_paths_for(&format!("{in_dir}/_static/**/*"))?,
          styles: resolved_paths_for(&format!("{in_dir}/_styles/**/*.css"))?,
@@ -524,6 +1304,7 @@ impl fmt::Display for SiteFiles {
       writeln!(f, "  config files:{}", self.config)?;
       writeln!(f, "  content files:{}", display_paths(&self.content))?;
       writeln!(f, "  data files:{}", display_paths(&self.data))?;
+      writeln!(f, "  templated files:{}", display_paths(&self.templated))?;
       writeln!(f, "  style files:{}", display_paths(&self.styles))?;
       writeln!(f, "  template files:{}", display_paths(&self.templates))?;
       Ok(())
@@ -592,3 +1373,140 @@ fn resolved_paths_for(glob_src: &str) -> Result<Vec<Utf8PathBuf>, Error> {
       })
       .map(|paths| paths.into_iter().filter(|path| path.is_file()).collect())
 }
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::test_support;
+
+   #[test]
+   fn templated_output_path_drops_lx() {
+      let content_dir = Utf8Path::new("/site/content");
+      assert_eq!(
+         templated_output_path(Utf8Path::new("/site/content/feed.lx.xml"), content_dir)
+            .unwrap(),
+         Utf8PathBuf::from("feed.xml")
+      );
+      assert_eq!(
+         templated_output_path(
+            Utf8Path::new("/site/content/journal/humans.lx.txt"),
+            content_dir
+         )
+         .unwrap(),
+         Utf8PathBuf::from("journal/humans.txt")
+      );
+   }
+
+   #[test]
+   fn templated_output_path_requires_lx_extension() {
+      assert!(
+         templated_output_path(
+            Utf8Path::new("/site/content/feed.xml"),
+            Utf8Path::new("/site/content")
+         )
+         .is_err()
+      );
+   }
+
+   #[test]
+   fn items_with_the_same_output_path_collide() {
+      let note = |name: &str, date: &str| {
+         test_support::source(
+            &format!("notes/{name}"),
+            &format!("---\ndate: {date}\n---\n\nA note.\n"),
+         )
+      };
+      let sources = [
+         note("a.md", "2025-06-22T09:15:00Z"),
+         note("b.md", "2025-06-22T09:15:10Z"),
+         note("c.md", "2025-06-22T09:15:10Z"),
+      ];
+      let items = sources.iter().map(test_support::item).collect::<Vec<_>>();
+
+      assert!(check_output_paths(&items[..2]).is_ok());
+      assert!(matches!(
+         check_output_paths(&items),
+         Err(Error::DuplicatePath { path, .. }) if path == "notes/2025-06-22-091510"
+      ));
+   }
+
+   #[test]
+   fn failures_include_the_template_line() {
+      let env = minijinja::Environment::new();
+      let template_error = env
+         .render_str("<p>\n{{ nope(1) }}\n</p>", ())
+         .expect_err("calling an unknown function fails");
+      let error = Error::Rewrite {
+         text: String::from("<p>"),
+         source: template_error,
+      };
+
+      let failures = error.failures();
+      assert_eq!(failures.len(), 1);
+
+      let failure = &failures[0];
+      assert_eq!(failure.path, None);
+      assert_eq!(failure.chain.len(), 2);
+
+      let line = failure.line.as_ref().expect("minijinja knows the line");
+      assert_eq!(line.file, "<string>");
+      assert_eq!(line.number, 2);
+      assert_eq!(line.text, "{{ nope(1) }}");
+   }
+
+   #[test]
+   fn rebuilding_a_moved_item_drops_its_old_output() {
+      let dir = std::env::temp_dir().join(format!("lx-rebuild-{}", std::process::id()));
+      let dir = Utf8PathBuf::try_from(dir).unwrap();
+      let page = |permalink: &str| {
+         format!("---\ntitle: Hello\npermalink: {permalink}\n---\n\nHi.\n")
+      };
+      fs::create_dir_all(dir.join("content")).unwrap();
+      fs::create_dir_all(dir.join(&*UI_DIR)).unwrap();
+      fs::write(
+         dir.join("config.lx.yaml"),
+         "url: https://example.com/\n\
+          repo: https://example.com/repo\n\
+          title: { normal: Example, stylized: Example }\n\
+          description: An example\n\
+          author: { name: Example, email: hi@example.com, links: {} }\n\
+          output: public\n\
+          image: { url: https://example.com/image.png }\n",
+      )
+      .unwrap();
+      fs::write(dir.join(&*UI_DIR).join("base.jinja"), "{{ content }}").unwrap();
+      fs::write(dir.join("content/hello.md"), page("hello")).unwrap();
+      fs::write(dir.join("content/about.md"), page("about")).unwrap();
+
+      let directory = Canonicalized::try_from(dir.as_path()).unwrap();
+      let config = config_for(&directory).unwrap();
+      let md = Markdown::new(None);
+      let cache = Cache::disabled();
+      let memory = Memory::default();
+      let input_dirs = InputDirs::for_site(&directory);
+      let changed = [input_dirs.changed(directory.as_ref().join("content/hello.md"))];
+
+      // Without a successful build to start from, even a single change builds the site.
+      rebuild(&directory, &config, &md, &cache, &memory, &changed).unwrap();
+      assert!(
+         memory
+            .snapshot()
+            .contains_key(Utf8Path::new("about/index.html"))
+      );
+      assert!(
+         memory
+            .snapshot()
+            .contains_key(Utf8Path::new("hello/index.html"))
+      );
+
+      fs::write(dir.join("content/hello.md"), page("goodbye")).unwrap();
+      let rebuilt = rebuild(&directory, &config, &md, &cache, &memory, &changed);
+      fs::remove_dir_all(&dir).unwrap();
+      rebuilt.unwrap();
+
+      let files = memory.snapshot();
+      assert!(files.contains_key(Utf8Path::new("about/index.html")));
+      assert!(files.contains_key(Utf8Path::new("goodbye/index.html")));
+      assert!(!files.contains_key(Utf8Path::new("hello/index.html")));
+   }
+}
@@ -0,0 +1,70 @@
+//! Checking a site for errors without writing anything, for `lx check`.
+
+use std::sync::Mutex;
+
+use chrono::Local;
+use lx_md::Markdown;
+
+use super::{
+   Error, Failure, Pass, config_for, incremental::Plan, output::Writer, render_site,
+};
+use crate::{cache::Cache, canonicalized::Canonicalized, publication};
+
+/// What to do with an error in one part of a build which does not keep the rest of the
+/// build from running.
+pub(super) enum OnError<'e> {
+   /// Fail the build with it.
+   Stop,
+
+   /// Keep it to report along with every other error, and carry on with whatever did
+   /// not fail.
+   Collect(&'e Mutex<Vec<Error>>),
+}
+
+impl OnError<'_> {
+   pub(super) fn handle(&self, error: Error) -> Result<(), Error> {
+      match self {
+         OnError::Stop => Err(error),
+         OnError::Collect(errors) => {
+            errors
+               .lock()
+               .expect("no panics while holding the lock")
+               .push(error);
+            Ok(())
+         }
+      }
+   }
+}
+
+/// Run every phase of a build of the site in `directory` without writing any output,
+/// and report every problem found along the way. An empty list means the site is good.
+/// Drafts and scheduled items are checked too, so they are ready once published.
+pub fn check(directory: &Canonicalized) -> Vec<Failure> {
+   let config = match config_for(directory) {
+      Ok(config) => config,
+      Err(error) => return error.failures(),
+   };
+
+   let errors = Mutex::new(Vec::new());
+   let result = render_site(
+      directory,
+      &config,
+      &Markdown::new(None),
+      &Cache::disabled(),
+      &Writer::discard(&config.output),
+      Pass {
+         plan: Plan::everything(),
+         publication: publication::Options::everything(Local::now().fixed_offset()),
+         on_error: OnError::Collect(&errors),
+      },
+   );
+
+   let mut errors = errors
+      .into_inner()
+      .expect("no panics while holding the lock");
+   if let Err(error) = result {
+      errors.push(error);
+   }
+
+   errors.iter().flat_map(Error::failures).collect()
+}
@@ -0,0 +1,288 @@
+//! A single problem which caused a build to fail, along with where in its source it
+//! happened, in a form which can be shown in the terminal or outside it, e.g. in the
+//! browser during development.
+
+use std::{error, fs, ops::Range};
+
+use camino::Utf8PathBuf;
+use miette::{LabeledSpan, MietteDiagnostic, NamedSource, Report};
+use serde::Serialize;
+
+use super::Error;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Failure {
+   /// The file with the problem, when it is known.
+   pub path: Option<Utf8PathBuf>,
+
+   /// The error message, followed by the messages of each of its underlying causes.
+   pub chain: Vec<String>,
+
+   /// The line where the problem occurred, when it is known. It may be in a different
+   /// file than `path`, e.g. in the template for a content file's layout.
+   pub line: Option<SourceLine>,
+
+   /// The source the problem occurred in, for showing it in context.
+   #[serde(skip)]
+   snippet: Option<Snippet>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SourceLine {
+   /// The path of the file or the name of the template the line is in.
+   pub file: String,
+   pub number: usize,
+   pub column: usize,
+   pub text: String,
+}
+
+#[derive(Debug, Clone)]
+struct Snippet {
+   /// The path of the file or the name of the template.
+   name: String,
+   contents: String,
+   span: Range<usize>,
+}
+
+impl Failure {
+   pub fn new(
+      path: Option<Utf8PathBuf>,
+      error: &(dyn error::Error + 'static),
+   ) -> Failure {
+      let snippet = path
+         .as_ref()
+         .and_then(|path| {
+            let contents = fs::read_to_string(path).ok()?;
+            let span = span_in_file(error, &contents)?;
+            Some(Snippet {
+               name: path.to_string(),
+               contents,
+               span,
+            })
+         })
+         .or_else(|| template_snippet(error));
+
+      Failure {
+         path,
+         chain: crate::error::chain(error),
+         line: snippet.as_ref().map(Snippet::line),
+         snippet,
+      }
+   }
+
+   /// A report of the failure for the terminal, showing the source around it when it is
+   /// known.
+   pub fn report(&self) -> Report {
+      let (message, causes) = self
+         .chain
+         .split_first()
+         .map(|(message, causes)| (message.clone(), causes))
+         .unwrap_or_default();
+
+      let Some(snippet) = &self.snippet else {
+         let message = match &self.path {
+            Some(path) => format!("{path}: {message}"),
+            None => message,
+         };
+         let mut diagnostic = MietteDiagnostic::new(message);
+         if !causes.is_empty() {
+            diagnostic = diagnostic.with_help(causes.join("\n"));
+         }
+         return Report::new(diagnostic);
+      };
+
+      // The innermost cause is the most specific, so it labels the source; any others
+      // explain how the build got there.
+      let (label, context) = causes
+         .split_last()
+         .map(|(label, context)| (Some(label.clone()), context))
+         .unwrap_or_default();
+
+      let mut diagnostic = MietteDiagnostic::new(message).with_label(
+         LabeledSpan::new_primary_with_span(label, snippet.span.clone()),
+      );
+      if !context.is_empty() {
+         diagnostic = diagnostic.with_help(context.join("\n"));
+      }
+
+      Report::new(diagnostic)
+         .with_source_code(NamedSource::new(&snippet.name, snippet.contents.clone()))
+   }
+}
+
+impl Snippet {
+   fn line(&self) -> SourceLine {
+      let before = &self.contents[..self.span.start];
+      let start = before.rfind('\n').map_or(0, |newline| newline + 1);
+      let text = self.contents[start..].lines().next().unwrap_or_default();
+
+      SourceLine {
+         file: self.name.clone(),
+         number: before.matches('\n').count() + 1,
+         column: before[start..].chars().count() + 1,
+         text: text.to_owned(),
+      }
+   }
+}
+
+/// Find where in a file with the given `contents` the first error in the chain which
+/// knows its location occurred.
+fn span_in_file(
+   root: &(dyn error::Error + 'static),
+   contents: &str,
+) -> Option<Range<usize>> {
+   let mut error = Some(root);
+   while let Some(current) = error {
+      if let Some(span) = known_span(current, contents) {
+         // An error at the very end of a file, e.g. an unclosed YAML list, is shown
+         // on the last line rather than past it.
+         let span = if span.start == contents.len() && !contents.is_empty() {
+            let last = contents.trim_end().len();
+            last..last
+         } else {
+            span
+         };
+
+         return Some(span).filter(|span| {
+            contents.is_char_boundary(span.start) && contents.is_char_boundary(span.end)
+         });
+      }
+
+      error = current.source();
+   }
+
+   None
+}
+
+fn known_span(
+   error: &(dyn error::Error + 'static),
+   contents: &str,
+) -> Option<Range<usize>> {
+   // Front matter is parsed so that its YAML errors have the line and column in the
+   // whole file, just like the YAML errors for the site config and cascade files.
+   if let Some(yaml_error) = error.downcast_ref::<serde_yaml::Error>() {
+      let location = yaml_error.location()?;
+      let offset = offset_at(contents, location.line(), location.column())?;
+      return Some(offset..offset);
+   }
+
+   if let Some(md_error) = error.downcast_ref::<lx_md::Error>() {
+      return md_error.range();
+   }
+
+   // Content is rewritten with minijinja one chunk of text at a time, so a template
+   // error is somewhere in the chunk, which is itself somewhere in the file.
+   if let Some(Error::Rewrite { text, source }) = error.downcast_ref::<Error>() {
+      let start = contents.find(text.as_str())?;
+      let span = template_span(source, text)?;
+      return Some(start + span.start..start + span.end);
+   }
+
+   None
+}
+
+/// The snippet for the first template error in the chain, for errors in templates
+/// rather than in content files.
+fn template_snippet(root: &(dyn error::Error + 'static)) -> Option<Snippet> {
+   let mut error = Some(root);
+   while let Some(current) = error {
+      if let Some(template_error) = current.downcast_ref::<minijinja::Error>()
+         && let Some(contents) = template_error.template_source()
+         && let Some(span) = template_span(template_error, contents)
+      {
+         return Some(Snippet {
+            name: template_error.name().unwrap_or("template").to_owned(),
+            contents: contents.to_owned(),
+            span,
+         });
+      }
+
+      error = current.source();
+   }
+
+   None
+}
+
+/// Where in its template `source` a template error occurred: its exact range if
+/// minijinja knows it, or else the whole line.
+fn template_span(error: &minijinja::Error, source: &str) -> Option<Range<usize>> {
+   if let Some(range) = error.range() {
+      return Some(range);
+   }
+
+   let start = offset_at(source, error.line()?, 1)?;
+   let line = source[start..].lines().next()?;
+   Some(start..start + line.len())
+}
+
+/// The byte offset in `contents` of a line and a column, both starting from 1, with the
+/// column counted in characters.
+fn offset_at(contents: &str, line: usize, column: usize) -> Option<usize> {
+   let start = contents
+      .split_inclusive('\n')
+      .take(line.checked_sub(1)?)
+      .map(str::len)
+      .sum::<usize>();
+
+   let rest = &contents[start..];
+   let within = rest
+      .char_indices()
+      .nth(column.saturating_sub(1))
+      .map_or(rest.len(), |(index, _)| index);
+
+   Some(start + within)
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::data::item::serial;
+
+   const PAGE: &str =
+      "---\ntitle: Café ☕️\ntags: [oops\ndate: 2025-01-01\n---\n\nHello, {{ nope( }}!\n";
+
+   fn line_at(span: Range<usize>) -> SourceLine {
+      Snippet {
+         name: String::from("page.md"),
+         contents: PAGE.to_owned(),
+         span,
+      }
+      .line()
+   }
+
+   #[test]
+   fn front_matter_errors_point_into_the_file() {
+      let metadata = PAGE.split("---\n").nth(1).unwrap();
+      let error = serial::Item::try_parse(metadata, PAGE).expect_err("bad YAML");
+
+      let span = span_in_file(&error, PAGE).expect("YAML errors have a location");
+      let line = line_at(span);
+      assert_eq!(line.file, "page.md");
+      assert_eq!(line.number, 4);
+      assert_eq!(line.column, 5);
+      assert_eq!(line.text, "date: 2025-01-01");
+   }
+
+   #[test]
+   fn rewrite_errors_point_into_the_file() {
+      let text = "Hello, {{ nope( }}!";
+      let source = minijinja::Environment::new()
+         .render_str(text, ())
+         .expect_err("bad template syntax");
+      let error = Error::Rewrite {
+         text: text.to_owned(),
+         source,
+      };
+
+      let span = span_in_file(&error, PAGE).expect("minijinja knows the range");
+      let line = line_at(span);
+      assert_eq!(line.number, 7);
+      assert_eq!(line.text, text);
+   }
+
+   #[test]
+   fn offsets_count_columns_in_characters() {
+      assert_eq!(offset_at(PAGE, 2, 13), PAGE.find('☕'));
+      assert_eq!(offset_at(PAGE, 1, 1), Some(0));
+   }
+}
@@ -0,0 +1,163 @@
+//! Working out which output a set of changed files requires writing again, so that
+//! `lx develop` does not re-render the whole site for every edit.
+//!
+//! Every rebuild still loads and renders all of the content, because aggregate pages
+//! (feeds, archives, taxonomies, etc.) need all of it. What it skips is *writing*
+//! output which cannot have changed.
+
+use std::collections::HashSet;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use minijinja::Environment;
+
+use super::input::{Changed, Input};
+use crate::page::Item;
+use crate::templates::dependencies::Dependencies;
+
+/// What to write during a build.
+#[derive(Debug, Default)]
+pub(super) struct Plan {
+   /// Write everything, e.g. for the initial build or after a config change.
+   everything: bool,
+
+   /// Content sources which changed. Any change to content also requires writing the
+   /// pages which aggregate it.
+   content: HashSet<Utf8PathBuf>,
+
+   /// Templated outputs (`*.lx.*`) whose sources changed.
+   templated: HashSet<Utf8PathBuf>,
+
+   /// Templates which changed. Once the templates are loaded, [`Plan::resolve`] expands
+   /// this to include every template which depends on them.
+   templates: HashSet<String>,
+
+   /// Templates which use the `site` global, and so aggregate every item.
+   aggregating: HashSet<String>,
+
+   /// Static files which changed.
+   static_files: HashSet<Utf8PathBuf>,
+
+   /// Whether any styles changed. Styles are compiled from their root files, which may
+   /// import any of the others, so a change to any of them rebuilds all of them.
+   styles: bool,
+}
+
+impl Plan {
+   pub(super) fn everything() -> Plan {
+      Plan {
+         everything: true,
+         ..Plan::default()
+      }
+   }
+
+   /// Plan a rebuild for the given changed paths. Anything which cannot be handled
+   /// granularly, including any removed file, means writing everything.
+   pub(super) fn for_changes(changed: &[Changed]) -> Plan {
+      let mut plan = Plan::default();
+      for Changed { path, input } in changed {
+         if !path.exists() {
+            return Plan::everything();
+         }
+
+         match input {
+            Input::Content => {
+               plan.content.insert(path.clone());
+            }
+            Input::Templated => {
+               plan.templated.insert(path.clone());
+            }
+            Input::Template(name) => {
+               plan.templates.insert(name.clone());
+            }
+            Input::Static => {
+               plan.static_files.insert(path.clone());
+            }
+            Input::Style => plan.styles = true,
+            Input::Config | Input::Data | Input::Other => return Plan::everything(),
+         }
+      }
+      plan
+   }
+
+   /// Expand the changed templates to everything which depends on them, now that the
+   /// templates are loaded.
+   pub(super) fn resolve(mut self, env: &Environment) -> Plan {
+      if self.everything {
+         return self;
+      }
+
+      let dependencies = Dependencies::new(env);
+      self.templates = dependencies.affected_by(&self.templates);
+      self.aggregating = dependencies.using_site();
+      self
+   }
+
+   /// The content sources which changed.
+   pub(super) fn changed_content(&self) -> impl Iterator<Item = &Utf8Path> {
+      self.content.iter().map(Utf8PathBuf::as_path)
+   }
+
+   pub(super) fn is_everything(&self) -> bool {
+      self.everything
+   }
+
+   /// Feeds only depend on the content.
+   pub(super) fn writes_feeds(&self) -> bool {
+      self.everything || !self.content.is_empty()
+   }
+
+   /// Pages which list items, e.g. archives and taxonomies: written when any content
+   /// changes, or when their layout does.
+   pub(super) fn writes_aggregate(&self, layout: &str) -> bool {
+      self.writes_feeds() || self.templates.contains(layout)
+   }
+
+   /// Templated outputs can use any item as well as other templates.
+   pub(super) fn writes_templated(&self, source: &Utf8Path, contents: &str) -> bool {
+      self.writes_feeds()
+         || self.templated.contains(source)
+         || crate::templates::dependencies::references(contents)
+            .iter()
+            .any(|name| self.templates.contains(name))
+   }
+
+   /// An item is written if it changed, if its layout changed, or if it shows data from
+   /// other items which may have changed: a paginated list, a layout which uses the
+   /// `site` global, or a series which includes a changed item.
+   pub(super) fn writes_item(&self, item: &Item, items: &[Item]) -> bool {
+      if self.everything
+         || self.content.contains(&item.source().path)
+         || self.templates.contains(item.layout())
+      {
+         return true;
+      }
+
+      if self.content.is_empty() {
+         return false;
+      }
+
+      item.data().paginate
+         || self.aggregating.contains(item.layout())
+         || self.shares_series_with_changed(item, items)
+   }
+
+   fn shares_series_with_changed(&self, item: &Item, items: &[Item]) -> bool {
+      let Some(name) = item.data().series.as_ref().and_then(|s| s.name.as_ref()) else {
+         return false;
+      };
+
+      items
+         .iter()
+         .filter(|other| self.content.contains(&other.source().path))
+         .filter_map(|other| other.data().series.as_ref()?.name.as_ref())
+         .any(|other_name| other_name == name)
+   }
+
+   pub(super) fn copies_static(&self, path: &Utf8Path) -> bool {
+      self.everything || self.static_files.contains(path)
+   }
+
+   pub(super) fn writes_styles(&self) -> bool {
+      self.everything || self.styles
+   }
+}
@@ -0,0 +1,142 @@
+//! The directories a build reads from, and what kind of input each path in them is.
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+use super::UI_DIR;
+use crate::canonicalized::Canonicalized;
+
+/// Every directory a build reads from: the site itself, and the `_shared` directory next
+/// to it, which supplies templates, static files, and styles for all sites.
+#[derive(Debug, Clone)]
+pub struct InputDirs {
+   pub site: Utf8PathBuf,
+   pub shared: Option<Utf8PathBuf>,
+}
+
+/// What kind of input a path is, which determines what a change to it affects.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Input {
+   /// The site's `config.lx.yaml`.
+   Config,
+   /// A Markdown content file.
+   Content,
+   /// A `_data.lx.yaml` file in the content cascade.
+   Data,
+   /// A templated output file, named like `<name>.lx.<ext>`.
+   Templated,
+   /// A template in a `_ui` directory, by its name in the template environment.
+   Template(String),
+   /// A file in a `_static` directory.
+   Static,
+   /// A file in a `_styles` directory.
+   Style,
+   /// Anything else.
+   Other,
+}
+
+/// A changed path, along with what kind of input it is.
+#[derive(Debug, Clone)]
+pub struct Changed {
+   pub path: Utf8PathBuf,
+   pub input: Input,
+}
+
+impl InputDirs {
+   pub fn for_site(site_dir: &Canonicalized) -> InputDirs {
+      let site = site_dir.as_ref().to_owned();
+      let shared = site.parent().map(|parent| parent.join("_shared"));
+      InputDirs { site, shared }
+   }
+
+   /// All the input directories which exist.
+   pub fn existing(&self) -> impl Iterator<Item = &Utf8Path> {
+      std::iter::once(self.site.as_path())
+         .chain(self.shared.as_deref())
+         .filter(|dir| dir.is_dir())
+   }
+
+   pub fn changed(&self, path: Utf8PathBuf) -> Changed {
+      let input = self.classify(&path);
+      Changed { path, input }
+   }
+
+   pub fn classify(&self, path: &Utf8Path) -> Input {
+      if path == self.site.join("config.lx.yaml") {
+         return Input::Config;
+      }
+
+      if let Ok(relative) = path.strip_prefix(self.site.join("content")) {
+         let name = relative.file_name().unwrap_or_default();
+         return if name == "_data.lx.yaml" {
+            Input::Data
+         } else if name.contains(".lx.") {
+            Input::Templated
+         } else if relative.extension() == Some("md") {
+            Input::Content
+         } else {
+            Input::Other
+         };
+      }
+
+      for root in std::iter::once(&self.site).chain(&self.shared) {
+         if let Ok(name) = path.strip_prefix(root.join(&*UI_DIR)) {
+            return Input::Template(name.to_string());
+         }
+
+         if path.starts_with(root.join("_static")) {
+            return Input::Static;
+         }
+
+         if path.starts_with(root.join("_styles")) {
+            return Input::Style;
+         }
+      }
+
+      Input::Other
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn kind(path: &str) -> Input {
+      let dirs = InputDirs {
+         site: Utf8PathBuf::from("/sites/v6"),
+         shared: Some(Utf8PathBuf::from("/sites/_shared")),
+      };
+      dirs.classify(Utf8Path::new(path))
+   }
+
+   #[test]
+   fn content() {
+      assert_eq!(kind("/sites/v6/content/journal/post.md"), Input::Content);
+      assert_eq!(kind("/sites/v6/content/feed.lx.xml"), Input::Templated);
+      assert_eq!(kind("/sites/v6/content/journal/_data.lx.yaml"), Input::Data);
+   }
+
+   #[test]
+   fn templates() {
+      assert_eq!(
+         kind("/sites/v6/_ui/includes/pagination.jinja"),
+         Input::Template(String::from("includes/pagination.jinja"))
+      );
+      assert_eq!(
+         kind("/sites/_shared/_ui/head.jinja"),
+         Input::Template(String::from("head.jinja"))
+      );
+   }
+
+   #[test]
+   fn static_files_and_styles() {
+      assert_eq!(kind("/sites/v6/_static/favicon.png"), Input::Static);
+      assert_eq!(kind("/sites/_shared/_styles/_fonts.css"), Input::Style);
+   }
+
+   #[test]
+   fn config_and_everything_else() {
+      assert_eq!(kind("/sites/v6/config.lx.yaml"), Input::Config);
+      assert_eq!(kind("/sites/_shared/config.lx.yaml"), Input::Other);
+      assert_eq!(kind("/sites/v6/README.md"), Input::Other);
+   }
+}
@@ -0,0 +1,319 @@
+//! Where a build writes its output: to disk for `lx publish`, to memory for
+//! `lx develop`, so that the server never sees a half-written site, or nowhere at all
+//! for `lx check`.
+
+use std::{
+   collections::{BTreeSet, HashMap},
+   fs,
+   sync::{Arc, Mutex, RwLock},
+};
+
+use axum::body::Bytes;
+use camino::{Utf8Path, Utf8PathBuf};
+
+use super::Error;
+
+/// Every output file, keyed by its path relative to the output directory.
+pub type Files = HashMap<Utf8PathBuf, Bytes>;
+
+/// The output files each content source produced, keyed by the source's path.
+pub type Sources = HashMap<Utf8PathBuf, BTreeSet<Utf8PathBuf>>;
+
+/// The output of the most recent successful build, held in memory.
+#[derive(Debug, Default)]
+pub struct Memory {
+   files: RwLock<Arc<Files>>,
+
+   /// Which source produced which files, or `None` until a build has succeeded.
+   sources: RwLock<Option<Arc<Sources>>>,
+}
+
+impl Memory {
+   /// The files from the most recent successful build. Holding onto the snapshot keeps
+   /// it alive even if a later build replaces it.
+   pub fn snapshot(&self) -> Arc<Files> {
+      Arc::clone(&self.files.read().expect("no panics while holding the lock"))
+   }
+
+   /// The output of each content source in the most recent successful build, if there
+   /// has been one.
+   pub(super) fn sources(&self) -> Option<Arc<Sources>> {
+      self
+         .sources
+         .read()
+         .expect("no panics while holding the lock")
+         .clone()
+   }
+
+   fn replace(&self, files: Files, sources: Sources) {
+      *self
+         .files
+         .write()
+         .expect("no panics while holding the lock") = Arc::new(files);
+      *self
+         .sources
+         .write()
+         .expect("no panics while holding the lock") = Some(Arc::new(sources));
+   }
+}
+
+/// The paths which differ between two sets of output: added, removed, or changed.
+pub fn changes(before: &Files, after: &Files) -> Vec<Utf8PathBuf> {
+   let changed_or_added = after
+      .iter()
+      .filter(|(path, content)| before.get(*path) != Some(*content))
+      .map(|(path, _)| path);
+
+   let removed = before.keys().filter(|path| !after.contains_key(*path));
+
+   let mut changes = changed_or_added.chain(removed).cloned().collect::<Vec<_>>();
+   changes.sort();
+   changes
+}
+
+/// Writes the output of a single build.
+pub(super) struct Writer<'m> {
+   root: &'m Utf8Path,
+   target: Target<'m>,
+}
+
+enum Target<'m> {
+   Disk,
+   Memory {
+      memory: &'m Memory,
+      staged: Mutex<Files>,
+      sources: Mutex<Sources>,
+   },
+   Discard,
+}
+
+impl<'m> Writer<'m> {
+   /// Write directly to the output directory on disk.
+   pub(super) fn disk(root: &'m Utf8Path) -> Result<Writer<'m>, Error> {
+      fs::create_dir_all(root).map_err(|source| Error::CreateDir {
+         path: root.to_owned(),
+         source,
+      })?;
+
+      Ok(Writer {
+         root,
+         target: Target::Disk,
+      })
+   }
+
+   /// Write to memory, starting from the given files and the sources which produced
+   /// them, and replace the output in `memory` only once the build
+   /// [finishes](Writer::finish).
+   pub(super) fn memory(
+      root: &'m Utf8Path,
+      memory: &'m Memory,
+      files: Files,
+      sources: Sources,
+   ) -> Writer<'m> {
+      Writer {
+         root,
+         target: Target::Memory {
+            memory,
+            staged: Mutex::new(files),
+            sources: Mutex::new(sources),
+         },
+      }
+   }
+
+   /// Write nothing at all, while still checking that every path is in the output
+   /// directory.
+   pub(super) fn discard(root: &'m Utf8Path) -> Writer<'m> {
+      Writer {
+         root,
+         target: Target::Discard,
+      }
+   }
+
+   /// Write `content` to `path`, which must be in the output directory.
+   pub(super) fn emit(
+      &self,
+      path: &Utf8Path,
+      content: impl AsRef<[u8]>,
+   ) -> Result<(), Error> {
+      match &self.target {
+         Target::Disk => {
+            let output_dir = path.parent().expect("must have a real parent");
+            fs::create_dir_all(output_dir).map_err(|source| {
+               Error::CreateOutputDirectory {
+                  path: output_dir.to_owned(),
+                  source,
+               }
+            })?;
+            fs::write(path, content).map_err(|source| Error::WriteFile {
+               path: path.to_owned(),
+               source,
+            })
+         }
+
+         Target::Memory { staged, .. } => {
+            let relative = self.relative(path)?;
+            staged
+               .lock()
+               .expect("no panics while holding the lock")
+               .insert(relative, Bytes::copy_from_slice(content.as_ref()));
+            Ok(())
+         }
+
+         Target::Discard => self.relative(path).map(|_| ()),
+      }
+   }
+
+   /// Write `content` to `path` like [`emit`](Writer::emit), recording that the content
+   /// source at `source` produced it.
+   pub(super) fn emit_for(
+      &self,
+      source: &Utf8Path,
+      path: &Utf8Path,
+      content: impl AsRef<[u8]>,
+   ) -> Result<(), Error> {
+      if let Target::Memory { sources, .. } = &self.target {
+         let relative = self.relative(path)?;
+         sources
+            .lock()
+            .expect("no panics while holding the lock")
+            .entry(source.to_owned())
+            .or_default()
+            .insert(relative);
+      }
+
+      self.emit(path, content)
+   }
+
+   /// The files the content source at `source` has produced so far. Only output in
+   /// memory keeps track of this.
+   pub(super) fn outputs_of(&self, source: &Utf8Path) -> BTreeSet<Utf8PathBuf> {
+      match &self.target {
+         Target::Memory { sources, .. } => sources
+            .lock()
+            .expect("no panics while holding the lock")
+            .get(source)
+            .cloned()
+            .unwrap_or_default(),
+         Target::Disk | Target::Discard => BTreeSet::new(),
+      }
+   }
+
+   /// Copy the file at `from` to `to`, which must be in the output directory.
+   pub(super) fn copy(&self, from: &Utf8Path, to: &Utf8Path) -> Result<(), Error> {
+      match &self.target {
+         Target::Disk => {
+            let output_dir = to.parent().expect("must have a real parent");
+            fs::create_dir_all(output_dir).map_err(|source| {
+               Error::CreateOutputDirectory {
+                  path: output_dir.to_owned(),
+                  source,
+               }
+            })?;
+            fs::copy(from, to).map_err(|source| Error::CopyFile {
+               from: from.to_owned(),
+               to: to.to_owned(),
+               source,
+            })?;
+            Ok(())
+         }
+
+         Target::Memory { .. } => {
+            let content = fs::read(from).map_err(|source| Error::CopyFile {
+               from: from.to_owned(),
+               to: to.to_owned(),
+               source,
+            })?;
+            self.emit(to, content)
+         }
+
+         Target::Discard => self.relative(to).map(|_| ()),
+      }
+   }
+
+   /// Make the output visible. For disk output, it already is; for memory output, this
+   /// atomically replaces the previous build.
+   pub(super) fn finish(self) {
+      if let Target::Memory {
+         memory,
+         staged,
+         sources,
+      } = self.target
+      {
+         memory.replace(
+            staged
+               .into_inner()
+               .expect("no panics while holding the lock"),
+            sources
+               .into_inner()
+               .expect("no panics while holding the lock"),
+         );
+      }
+   }
+
+   fn relative(&self, path: &Utf8Path) -> Result<Utf8PathBuf, Error> {
+      path
+         .strip_prefix(self.root)
+         .map(Utf8Path::to_owned)
+         .map_err(|_| Error::StripPrefix {
+            prefix: self.root.to_owned(),
+            path: path.to_owned(),
+         })
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn memory_output_is_only_visible_once_finished() {
+      let memory = Memory::default();
+      let root = Utf8Path::new("/site/public");
+
+      let writer = Writer::memory(root, &memory, Files::new(), Sources::new());
+      writer.emit(&root.join("index.html"), "hello").unwrap();
+      assert!(memory.snapshot().is_empty());
+
+      writer.finish();
+      assert_eq!(
+         memory.snapshot().get(Utf8Path::new("index.html")),
+         Some(&Bytes::from("hello"))
+      );
+
+      let before = memory.snapshot();
+      let writer = Writer::memory(root, &memory, before.as_ref().clone(), Sources::new());
+      writer
+         .emit(&root.join("about/index.html"), "about")
+         .unwrap();
+      writer.finish();
+
+      assert_eq!(before.len(), 1, "earlier snapshots are unaffected");
+      assert_eq!(memory.snapshot().len(), 2);
+   }
+
+   #[test]
+   fn changes_between_builds() {
+      let before = Files::from([
+         (Utf8PathBuf::from("index.html"), Bytes::from("home")),
+         (Utf8PathBuf::from("style.css"), Bytes::from("body {}")),
+         (Utf8PathBuf::from("old/index.html"), Bytes::from("old")),
+      ]);
+      let after = Files::from([
+         (Utf8PathBuf::from("index.html"), Bytes::from("home")),
+         (
+            Utf8PathBuf::from("style.css"),
+            Bytes::from("body { margin: 0 }"),
+         ),
+         (Utf8PathBuf::from("new/index.html"), Bytes::from("new")),
+      ]);
+
+      assert_eq!(
+         changes(&before, &after),
+         vec![
+            Utf8PathBuf::from("new/index.html"),
+            Utf8PathBuf::from("old/index.html"),
+            Utf8PathBuf::from("style.css"),
+         ]
+      );
+   }
+}
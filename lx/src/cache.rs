@@ -0,0 +1,261 @@
+//! A persistent, on-disk cache of prepared and rendered items, so that builds can skip
+//! re-parsing and re-highlighting content which has not changed.
+//!
+//! Each entry is keyed by a hash of its own source along with everything else which can
+//! change how it renders: the site config, the cascade data, the syntax set, and the
+//! templates. A change to any of those shared inputs invalidates every entry, which is
+//! the conservative choice: content can reference any template via components.
+
+use std::{collections::HashSet, fs, io, sync::Mutex};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use log::{debug, warn};
+use minijinja::Environment;
+use syntect::parsing::SyntaxSet;
+use thiserror::Error;
+
+use crate::{
+   data::config::Config,
+   page::{Rendered, Source},
+};
+
+/// The default location for the cache, relative to the site directory.
+pub const DIR_NAME: &str = ".lx-cache";
+
+pub struct Cache {
+   dir: Option<Utf8PathBuf>,
+}
+
+impl Cache {
+   /// Use the cache in the given site directory.
+   pub fn for_site(site_dir: &Utf8Path) -> Cache {
+      Cache {
+         dir: Some(site_dir.join(DIR_NAME)),
+      }
+   }
+
+   /// Skip the cache entirely: never read from it or write to it.
+   pub fn disabled() -> Cache {
+      Cache { dir: None }
+   }
+
+   /// The entries in the cache which are valid for the given shared inputs.
+   pub fn entries(&self, inputs: Inputs) -> Entries<'_> {
+      Entries {
+         dir: self.dir.as_deref(),
+         inputs: inputs.0.finalize(),
+         used: Mutex::new(HashSet::new()),
+      }
+   }
+}
+
+/// The inputs shared by every item in a build.
+pub struct Inputs(blake3::Hasher);
+
+impl Inputs {
+   pub fn new(
+      config: &Config,
+      cascade_files: &[Utf8PathBuf],
+      syntax_set: &SyntaxSet,
+      env: &Environment,
+   ) -> Result<Inputs, Error> {
+      let mut hasher = blake3::Hasher::new();
+      hasher.update(env!("CARGO_PKG_VERSION").as_bytes());
+
+      // Going via `Value` sorts the keys of any maps in the config, so the hash does not
+      // depend on their (random) iteration order.
+      let config = serde_json::to_value(config)
+         .and_then(|value| serde_json::to_vec(&value))
+         .map_err(Error::Config)?;
+      hasher.update(&config);
+
+      for path in cascade_files {
+         let contents = fs::read(path).map_err(|source| Error::Read {
+            path: path.clone(),
+            source,
+         })?;
+         hasher.update(path.as_str().as_bytes());
+         hasher.update(&contents);
+      }
+
+      for syntax in syntax_set.syntaxes() {
+         hasher.update(syntax.name.as_bytes());
+         hasher.update(syntax.scope.build_string().as_bytes());
+         for extension in &syntax.file_extensions {
+            hasher.update(extension.as_bytes());
+         }
+      }
+
+      let mut templates = env.templates().collect::<Vec<_>>();
+      templates.sort_by_key(|(name, _)| *name);
+      for (name, template) in templates {
+         hasher.update(name.as_bytes());
+         hasher.update(template.source().as_bytes());
+      }
+
+      Ok(Inputs(hasher))
+   }
+}
+
+/// Cache entries for a single set of [`Inputs`].
+pub struct Entries<'c> {
+   dir: Option<&'c Utf8Path>,
+   inputs: blake3::Hash,
+   used: Mutex<HashSet<Utf8PathBuf>>,
+}
+
+impl Entries<'_> {
+   /// The cached rendering of the source, if there is a valid one. An entry which cannot
+   /// be read is treated as missing, so a corrupt cache only costs a re-render.
+   pub fn get(&self, source: &Source) -> Option<Rendered> {
+      let path = self.path_for(source)?;
+      let contents = match fs::read(&path) {
+         Ok(contents) => contents,
+         Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+         Err(e) => {
+            debug!("could not read cache entry {path}: {e}");
+            return None;
+         }
+      };
+
+      match serde_json::from_slice(&contents) {
+         Ok(rendered) => Some(rendered),
+         Err(e) => {
+            debug!("could not parse cache entry {path}: {e}");
+            None
+         }
+      }
+   }
+
+   /// Store the rendering of the source. Failing to write the cache does not fail the
+   /// build, since the cache is only ever an optimization.
+   pub fn put(&self, source: &Source, rendered: &Rendered) {
+      let Some(path) = self.path_for(source) else {
+         return;
+      };
+
+      let result = serde_json::to_vec(rendered)
+         .map_err(io::Error::other)
+         .and_then(|contents| {
+            fs::create_dir_all(path.parent().expect("entries are in the cache dir"))?;
+            fs::write(&path, contents)
+         });
+
+      if let Err(e) = result {
+         warn!("could not write cache entry {path}: {e}");
+      }
+   }
+
+   /// Remove every entry which was not used since these entries were created. Every
+   /// build renders every item, so anything else is stale.
+   pub fn prune(&self) {
+      let Some(dir) = self.dir else {
+         return;
+      };
+
+      let used = self.used.lock().expect("no panics while holding the lock");
+      let stale = glob::glob(dir.join("*/*").as_str())
+         .expect("the pattern is valid")
+         .filter_map(Result::ok)
+         .filter_map(|path| Utf8PathBuf::try_from(path).ok())
+         .filter(|path| !used.contains(path));
+
+      let mut count = 0;
+      for path in stale {
+         match fs::remove_file(&path) {
+            Ok(()) => count += 1,
+            Err(e) => warn!("could not remove stale cache entry {path}: {e}"),
+         }
+      }
+
+      debug!("pruned {count} stale cache entries");
+   }
+
+   fn path_for(&self, source: &Source) -> Option<Utf8PathBuf> {
+      let dir = self.dir?;
+      let mut hasher = blake3::Hasher::new();
+      hasher.update(self.inputs.as_bytes());
+      hasher.update(source.path.as_str().as_bytes());
+      hasher.update(source.contents.as_bytes());
+      let key = hasher.finalize().to_hex();
+      let path = dir.join(&key[..2]).join(key.as_str());
+
+      self
+         .used
+         .lock()
+         .expect("no panics while holding the lock")
+         .insert(path.clone());
+
+      Some(path)
+   }
+}
+
+#[derive(Error, Debug)]
+pub enum Error {
+   #[error("could not read {path} to compute the cache key")]
+   Read {
+      path: Utf8PathBuf,
+      source: io::Error,
+   },
+
+   #[error("could not serialize the config to compute the cache key")]
+   Config(#[source] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::test_support;
+
+   fn source(contents: &str) -> Source {
+      test_support::source("post.md", contents)
+   }
+
+   fn entries(cache: &Cache) -> Entries<'_> {
+      Entries {
+         dir: cache.dir.as_deref(),
+         inputs: blake3::hash(b"inputs"),
+         used: Mutex::new(HashSet::new()),
+      }
+   }
+
+   fn test_dir(name: &str) -> Utf8PathBuf {
+      let dir =
+         std::env::temp_dir().join(format!("lx-cache-{name}-{}", std::process::id()));
+      Utf8PathBuf::try_from(dir).unwrap()
+   }
+
+   #[test]
+   fn round_trip() {
+      let dir = test_dir("round-trip");
+      let cache = Cache {
+         dir: Some(dir.clone()),
+      };
+      let entries = entries(&cache);
+
+      let source = source("---\ntitle: Hello\n---\n\nSome *content*.");
+      let rendered = test_support::rendered(&source);
+      entries.put(&source, &rendered);
+
+      let cached = entries.get(&source).expect("the entry was just written");
+      assert_eq!(
+         serde_json::to_value(&cached).unwrap(),
+         serde_json::to_value(&rendered).unwrap()
+      );
+
+      let changed = self::source("---\ntitle: Hello\n---\n\nOther *content*.");
+      assert!(entries.get(&changed).is_none());
+
+      fs::remove_dir_all(dir).unwrap();
+   }
+
+   #[test]
+   fn disabled() {
+      let cache = Cache::disabled();
+      let entries = entries(&cache);
+
+      let source = source("---\ntitle: Hello\n---\n\nSome *content*.");
+      entries.put(&source, &test_support::rendered(&source));
+      assert!(entries.get(&source).is_none());
+   }
+}
@@ -0,0 +1,288 @@
+//! Scaffolding new content, for `lx create`: each kind of content the site has is set
+//! up under `content_types` in the site config, e.g.
+//!
+//! ```yaml
+//! content_types:
+//!   journal:
+//!     directory: journal
+//!   note:
+//!     directory: notes
+//!     slug: date
+//!     front_matter:
+//!       tags: []
+//! ```
+
+use std::{
+   collections::HashMap,
+   fs::{self, OpenOptions},
+   io::{self, Write as _},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::{DateTime, FixedOffset};
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use thiserror::Error;
+
+use crate::{
+   data::{
+      config::Config,
+      item::{self, NOTE_SLUG_FORMAT, Slug},
+   },
+   page::{self, RootedPath},
+};
+
+/// The kinds of content `lx create` can make, by name.
+pub type ContentTypes = HashMap<String, ContentType>;
+
+/// One kind of content, as set in `content_types` in the site config.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContentType {
+   /// Where new items go, relative to the content directory.
+   pub directory: Utf8PathBuf,
+
+   /// How to name new items, and therefore what their slugs are.
+   #[serde(default)]
+   pub slug: SlugStrategy,
+
+   /// Front matter for every new item, after its title and dates.
+   #[serde(default)]
+   pub front_matter: Mapping,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SlugStrategy {
+   /// Name the file for the title, e.g. `My Post.md`, with the slug `my-post`. New
+   /// items of this kind must have a title.
+   #[default]
+   Title,
+
+   /// Name the file for when it was created, e.g. `2025-06-22-091500.md`, with that
+   /// as the slug. New items of this kind do not need a title.
+   Date,
+}
+
+/// A newly-created item.
+#[derive(Debug)]
+pub struct Created {
+   pub path: Utf8PathBuf,
+   pub url: String,
+}
+
+/// Create a new item of the `kind` named in the config for the site in `site_dir`,
+/// dated `now`.
+pub fn create(
+   site_dir: &Utf8Path,
+   config: &Config,
+   kind: &str,
+   title: Option<&str>,
+   now: DateTime<FixedOffset>,
+) -> Result<Created, Error> {
+   let content_type =
+      config
+         .content_types
+         .get(kind)
+         .ok_or_else(|| Error::UnknownContentType {
+            kind: kind.to_owned(),
+            known: known(&config.content_types),
+         })?;
+
+   let file_stem = match (content_type.slug, title) {
+      (SlugStrategy::Title, Some(title)) => title.replace('/', "-"),
+      (SlugStrategy::Title, None) => {
+         return Err(Error::MissingTitle {
+            kind: kind.to_owned(),
+         });
+      }
+      (SlugStrategy::Date, _) => now.format(NOTE_SLUG_FORMAT).to_string(),
+   };
+
+   let content_dir = site_dir.join("content");
+   // Not `with_extension`, which would treat e.g. the `.80` in “Rust 1.80” as one.
+   let path = content_dir
+      .join(&content_type.directory)
+      .join(format!("{file_stem}.md"));
+
+   let slug = Slug::new(None, &path)?;
+   let url = RootedPath::new(&slug, &content_dir)?.url(config);
+
+   let contents = scaffold(content_type, title, now)?;
+   write_new(&path, &contents)?;
+
+   Ok(Created { path, url })
+}
+
+/// The contents of a new item: its front matter, and nothing else yet.
+fn scaffold(
+   content_type: &ContentType,
+   title: Option<&str>,
+   now: DateTime<FixedOffset>,
+) -> Result<String, Error> {
+   let date = Value::from(now.format("%Y-%m-%dT%H:%M:%S%z").to_string());
+
+   let mut front_matter = Mapping::new();
+   if let Some(title) = title {
+      front_matter.insert(Value::from("title"), Value::from(title));
+   }
+   front_matter.insert(Value::from("date"), date.clone());
+   front_matter.insert(Value::from("started"), date);
+   for (key, value) in &content_type.front_matter {
+      front_matter.insert(key.clone(), value.clone());
+   }
+
+   let yaml = serde_yaml::to_string(&front_matter)?;
+   Ok(format!("---\n{yaml}---\n\n"))
+}
+
+fn write_new(path: &Utf8Path, contents: &str) -> Result<(), Error> {
+   if let Some(dir) = path.parent() {
+      fs::create_dir_all(dir).map_err(|source| Error::CreateDir {
+         path: dir.to_owned(),
+         source,
+      })?;
+   }
+
+   // Opening with `create_new` means there is no window between checking whether the
+   // file exists and creating it.
+   let mut file = OpenOptions::new()
+      .write(true)
+      .create_new(true)
+      .open(path)
+      .map_err(|source| match source.kind() {
+         io::ErrorKind::AlreadyExists => Error::Exists {
+            path: path.to_owned(),
+         },
+         _ => Error::Write {
+            path: path.to_owned(),
+            source,
+         },
+      })?;
+
+   file
+      .write_all(contents.as_bytes())
+      .map_err(|source| Error::Write {
+         path: path.to_owned(),
+         source,
+      })
+}
+
+fn known(content_types: &ContentTypes) -> String {
+   let mut names = content_types.keys().map(String::as_str).collect::<Vec<_>>();
+   names.sort_unstable();
+   if names.is_empty() {
+      String::from("(none)")
+   } else {
+      names.join(", ")
+   }
+}
+
+#[derive(Error, Debug)]
+pub enum Error {
+   #[error("no content type named '{kind}' in the site config (known: {known})")]
+   UnknownContentType { kind: String, known: String },
+
+   #[error("new items of type '{kind}' are named for their titles, so need a title")]
+   MissingTitle { kind: String },
+
+   #[error("'{path}' already exists")]
+   Exists { path: Utf8PathBuf },
+
+   #[error("could not create directory '{path}'")]
+   CreateDir {
+      path: Utf8PathBuf,
+      source: io::Error,
+   },
+
+   #[error("could not write '{path}'")]
+   Write {
+      path: Utf8PathBuf,
+      source: io::Error,
+   },
+
+   #[error("could not build the front matter")]
+   FrontMatter {
+      #[from]
+      source: serde_yaml::Error,
+   },
+
+   #[error("could not build the slug")]
+   Slug {
+      #[from]
+      source: item::Error,
+   },
+
+   #[error("could not build the URL")]
+   Url {
+      #[from]
+      source: page::Error,
+   },
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::test_support;
+
+   fn now() -> DateTime<FixedOffset> {
+      DateTime::parse_from_rfc3339("2025-06-22T09:15:00-06:00").unwrap()
+   }
+
+   #[test]
+   fn scaffold_dates_and_extra_front_matter() {
+      let content_type: ContentType =
+         serde_yaml::from_str("directory: notes\nfront_matter:\n  tags: [rust]\n")
+            .unwrap();
+
+      let scaffolded = scaffold(&content_type, Some("Hello: world"), now()).unwrap();
+      assert_eq!(
+         scaffolded,
+         "---\ntitle: 'Hello: world'\ndate: 2025-06-22T09:15:00-0600\nstarted: 2025-06-22T09:15:00-0600\ntags:\n- rust\n---\n\n"
+      );
+
+      let metadata = scaffolded.split("---\n").nth(1).unwrap();
+      let parsed = item::serial::Item::try_parse(metadata, &scaffolded).unwrap();
+      assert_eq!(parsed.date, Some(now()));
+      assert_eq!(parsed.started, Some(now()));
+   }
+
+   #[test]
+   fn dotted_titles_keep_their_dots() {
+      let config = test_support::config_with(
+         "content_types:\n  journal:\n    directory: journal\n",
+      );
+
+      let dir =
+         std::env::temp_dir().join(format!("lx-create-dots-{}", std::process::id()));
+      let site_dir = Utf8PathBuf::try_from(dir.clone()).unwrap();
+      let created = create(
+         &site_dir,
+         &config,
+         "journal",
+         Some("Rust 1.80 is out"),
+         now(),
+      )
+      .unwrap();
+      fs::remove_dir_all(&dir).unwrap();
+
+      assert_eq!(
+         created.path,
+         site_dir.join("content/journal/Rust 1.80 is out.md")
+      );
+      assert_eq!(created.url, "https://example.com/journal/rust-1-80-is-out");
+   }
+
+   #[test]
+   fn refuses_to_overwrite() {
+      let dir = std::env::temp_dir().join(format!("lx-create-{}", std::process::id()));
+      let path = Utf8PathBuf::try_from(dir.join("notes/note.md")).unwrap();
+
+      write_new(&path, "first").unwrap();
+      let second = write_new(&path, "second");
+      let contents = fs::read_to_string(&path).unwrap();
+      fs::remove_dir_all(&dir).unwrap();
+
+      assert!(matches!(second, Err(Error::Exists { .. })));
+      assert_eq!(contents, "first");
+   }
+}
@@ -2,6 +2,7 @@ use camino::{Utf8Path, Utf8PathBuf};
 use serde::{Deserialize, Serialize};
 
 use super::image::Image;
+use crate::{archive, create, links, pagination, taxonomy::Taxonomy};
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
@@ -15,6 +16,16 @@ pub struct Config {
    pub image: Image,
    #[serde(default)]
    pub nav: Vec<NavItem>,
+   #[serde(default)]
+   pub taxonomies: Vec<Taxonomy>,
+   #[serde(default)]
+   pub archive: archive::Options,
+   #[serde(default)]
+   pub pagination: pagination::Options,
+   #[serde(default)]
+   pub links: links::Options,
+   #[serde(default)]
+   pub content_types: create::ContentTypes,
 }
 
 impl Config {
@@ -30,6 +41,11 @@ impl Config {
          output: serial_cfg.output,
          image: Image::from(serial_cfg.image),
          nav: serial_cfg.nav,
+         taxonomies: serial_cfg.taxonomies,
+         archive: serial_cfg.archive,
+         pagination: serial_cfg.pagination,
+         links: serial_cfg.links,
+         content_types: serial_cfg.content_types,
       })
    }
 }
@@ -47,12 +63,12 @@ pub mod serial {
    use std::{collections::HashMap, fmt::Display, sync::Arc};
 
    use camino::{Utf8Path, Utf8PathBuf};
-   use minijinja::{Environment, State, Value, value::Object};
+   use minijinja::{State, Value, value::Object};
    use normalize_path::NormalizePath as _;
    use serde::{Deserialize, Serialize};
    use thiserror::Error;
 
-   use crate::{data::email::Email, templates::component::Component};
+   use crate::{data::email::Email, taxonomy::Taxonomy, templates::component::Component};
 
    #[derive(Serialize, Deserialize, Debug)]
    pub struct Config {
@@ -66,6 +82,16 @@ pub mod serial {
       pub image: crate::data::image::serial::Image,
       #[serde(default)]
       pub nav: Vec<NavItem>,
+      #[serde(default)]
+      pub taxonomies: Vec<Taxonomy>,
+      #[serde(default)]
+      pub archive: crate::archive::Options,
+      #[serde(default)]
+      pub pagination: crate::pagination::Options,
+      #[serde(default)]
+      pub links: crate::links::Options,
+      #[serde(default)]
+      pub content_types: crate::create::ContentTypes,
    }
 
    impl Config {
@@ -89,6 +115,18 @@ pub mod serial {
             .normalize()
             .try_into()?;
 
+         // Every item's `tags` are already a taxonomy, with their own pages.
+         if let Some(taxonomy) = config
+            .taxonomies
+            .iter()
+            .find(|taxonomy| taxonomy.name == Taxonomy::tags().name)
+         {
+            return Err(Error::ReservedTaxonomy {
+               path: path.to_owned(),
+               name: taxonomy.name.clone(),
+            });
+         }
+
          Ok(config)
       }
    }
@@ -109,6 +147,8 @@ pub mod serial {
    pub struct Author {
       pub name: String,
       pub email: Email,
+      /// URL for an avatar image, used e.g. in feeds.
+      pub avatar: Option<String>,
       pub links: HashMap<String, String>,
    }
 
@@ -163,5 +203,8 @@ pub mod serial {
          #[from]
          source: camino::FromPathBufError,
       },
+
+      #[error("{path} configures a taxonomy named '{name}', but that name is built in")]
+      ReservedTaxonomy { path: Utf8PathBuf, name: String },
    }
 }
@@ -12,6 +12,18 @@ use crate::data::image::serial::Image;
 
 use super::serial::*;
 
+fn load_metadata(path: &Utf8Path) -> Result<Ambient, CascadeLoadError> {
+   let fd = std::fs::File::open(path).map_err(|e| CascadeLoadError::OpenFile {
+      source: e,
+      file: path.to_owned(),
+   })?;
+
+   serde_yaml::from_reader(&fd).map_err(|e| CascadeLoadError::ParseMetadata {
+      source: Box::new(e),
+      file: path.to_owned(),
+   })
+}
+
 // NOTE: this is currently quite naïve and in fact *wrong* as a result: what I
 // will actually need is a *tree*, where each point in the tree has two pieces
 // of info: the path to that point, and the Metadata for that point. The path
@@ -38,33 +50,38 @@ pub enum CascadeLoadError {
 
 impl Cascade {
    pub fn new(paths: &[Utf8PathBuf]) -> Result<Self, CascadeLoadError> {
+      let (cascade, mut errors) = Cascade::load(paths);
+      if errors.is_empty() {
+         Ok(cascade)
+      } else {
+         Err(errors.remove(0))
+      }
+   }
+
+   /// Load every file in `paths` which can be loaded, along with the errors for any
+   /// which cannot, so that all of them can be reported at once.
+   pub fn load(paths: &[Utf8PathBuf]) -> (Self, Vec<CascadeLoadError>) {
       let mut cascade = Cascade {
          inner: HashMap::new(),
       };
+      let mut errors = Vec::new();
 
       for path in paths {
-         let fd = std::fs::File::open(path).map_err(|e| CascadeLoadError::OpenFile {
-            source: e,
-            file: path.clone(),
-         })?;
-
-         let metadata: Ambient = serde_yaml::from_reader(&fd).map_err(|e| {
-            CascadeLoadError::ParseMetadata {
-               source: Box::new(e),
-               file: path.clone(),
+         match load_metadata(path) {
+            Ok(metadata) => {
+               // Panic instead of returning a `Result` because this means there is
+               // a real bug in the path construction (not something missing on disk).
+               let context_dir = path
+                  .parent()
+                  .unwrap_or_else(|| panic!("missing parent of path {path}"));
+
+               cascade.add_at(context_dir, metadata);
             }
-         })?;
-
-         // Panic instead of returning a `Result` because this means there is
-         // a real bug in the path construction (not something missing on disk).
-         let context_dir = path
-            .parent()
-            .unwrap_or_else(|| panic!("missing parent of path {path}"));
-
-         cascade.add_at(context_dir, metadata);
+            Err(error) => errors.push(error),
+         }
       }
 
-      Ok(cascade)
+      (cascade, errors)
    }
 
    pub fn add_at<P: AsRef<Utf8Path>>(&mut self, path: P, value: Ambient) -> &mut Self {
@@ -82,6 +99,10 @@ impl Cascade {
       self.find_map(p.as_ref(), &|m| m.layout.clone())
    }
 
+   pub fn link_layout<P: AsRef<Utf8Path>>(&self, p: P) -> Option<String> {
+      self.find_map(p.as_ref(), &|m| m.link_layout.clone())
+   }
+
    pub fn qualifiers<P: AsRef<Utf8Path>>(&self, p: P) -> Option<Qualifiers> {
       self.find_map(p.as_ref(), &|m| m.qualifiers.clone())
    }
@@ -100,6 +121,16 @@ impl Cascade {
       self.find_map(p.as_ref(), &|m| m.subscribe.clone())
    }
 
+   /// Every directory which directly declares its own feed subscriptions.
+   pub fn subscriptions(&self) -> impl Iterator<Item = (&Utf8Path, &Subscribe)> {
+      self.inner.iter().filter_map(|(path, ambient)| {
+         ambient
+            .subscribe
+            .as_ref()
+            .map(|subscribe| (path.as_path(), subscribe))
+      })
+   }
+
    pub fn image<P: AsRef<Utf8Path>>(&self, p: P) -> Option<Image> {
       self.find_map(p.as_ref(), &|m| m.image.clone())
    }
@@ -214,4 +245,32 @@ mod tests {
       );
       assert_eq!(cascade.layout("path"), None);
    }
+
+   #[test]
+   fn load_collects_every_error() {
+      let dir = std::env::temp_dir().join(format!("lx-cascade-{}", std::process::id()));
+      let dir = Utf8PathBuf::try_from(dir).unwrap();
+      let good = dir.join("good/_data.lx.yaml");
+      let bad = dir.join("bad/_data.lx.yaml");
+      let missing = dir.join("missing/_data.lx.yaml");
+      std::fs::create_dir_all(good.parent().unwrap()).unwrap();
+      std::fs::create_dir_all(bad.parent().unwrap()).unwrap();
+      std::fs::write(&good, "layout: post.jinja\n").unwrap();
+      std::fs::write(&bad, "layout: [\n").unwrap();
+
+      let (cascade, errors) = Cascade::load(&[good, bad, missing]);
+      std::fs::remove_dir_all(&dir).unwrap();
+
+      assert_eq!(
+         cascade.layout(dir.join("good/post.md")),
+         Some(String::from("post.jinja"))
+      );
+      assert!(matches!(
+         errors.as_slice(),
+         [
+            CascadeLoadError::ParseMetadata { .. },
+            CascadeLoadError::OpenFile { .. }
+         ]
+      ));
+   }
 }
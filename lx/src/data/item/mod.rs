@@ -1,10 +1,15 @@
 pub mod cascade;
 pub mod serial;
 
-use std::{collections::HashMap, fmt, path::StripPrefixError};
+use std::{
+   collections::{BTreeMap, HashMap},
+   fmt,
+   path::StripPrefixError,
+};
 
 use camino::{Utf8Path, Utf8PathBuf};
 use chrono::{DateTime, FixedOffset};
+use log::warn;
 use lx_md::Markdown;
 use minijinja::Environment;
 use serde::{Deserialize, Serialize};
@@ -13,8 +18,9 @@ use thiserror::Error;
 
 use super::image::Image;
 use crate::{
-   archive::Archive,
+   archive::{self, Archive},
    page::{self, Item},
+   taxonomy::{self, Taxonomy, Term},
    templates::component::Component,
 };
 
@@ -23,8 +29,8 @@ use self::cascade::Cascade;
 /// Shared data for both [`Post`]s and [`Page`]s.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Metadata {
-   /// The title of the item.
-   pub title: String,
+   /// The title of the item. Only notes, which must have a date instead, have none.
+   pub title: Option<String>,
 
    /// The path to this piece of content.
    pub slug: Slug,
@@ -33,17 +39,23 @@ pub struct Metadata {
    pub layout: String,
 
    pub book: Option<Book>,
+   /// Drafts are only included in builds for development or preview.
+   pub draft: bool,
    pub featured: bool,
    pub image: Option<Image>, // TODO: make it `Image`, not `Option`, and generate it.
 
-   /// For link items, the URL to the “target” post.
-   pub link: Option<String>,
+   /// For link items, the “target” post.
+   pub link: Option<Link>,
+   /// Should this item be rendered once per page of the posts in its directory?
+   pub paginate: bool,
    pub qualifiers: Qualifiers,
    pub series: Option<serial::Series>,
    pub subscribe: Option<serial::Subscribe>,
    pub subtitle: Option<Rendered>,
    pub summary: Option<Rendered>,
    pub tags: Vec<String>,
+   /// Terms for each of the site's configured taxonomies, keyed by taxonomy name.
+   pub taxonomies: BTreeMap<String, Vec<Term>>,
    pub thanks: Option<Rendered>,
    pub updated: Vec<Update>,
    pub work: Option<MusicalWork>,
@@ -54,6 +66,7 @@ impl Metadata {
       item: serial::Item,
       source: &page::Source,
       cascade: &Cascade,
+      taxonomies: &[Taxonomy],
       default_template_name: String,
       md: &Markdown,
    ) -> Result<(Self, Option<DateTime<FixedOffset>>), Error> {
@@ -71,21 +84,42 @@ impl Metadata {
 
       let work = MusicalWork::resolved(item.work, cascade.work(dir))?;
 
-      let title = work
-         .as_ref()
-         .map(|work| work.title.clone())
-         .or(item.title)
-         .ok_or_else(|| Error::MissingRequiredField { name: "title" })?;
+      let title = work.as_ref().map(|work| work.title.clone()).or(item.title);
+      if title.is_none() && item.date.is_none() {
+         return Err(Error::MissingRequiredField { name: "title" });
+      }
+
+      let unknown = unknown_fields(&item.taxonomies, taxonomies);
+      if !unknown.is_empty() {
+         warn!(
+            "{}: ignoring unknown fields, which are not taxonomies in the site config \
+             either: {}",
+            source.path,
+            unknown.join(", ")
+         );
+      }
 
       let render = |s: String| Rendered::markdown(&s, md);
 
+      // I don’t *think* the link makes sense to have in the cascade, but link items can
+      // get their own layout from it.
+      let link = item.link.map(Link::try_from).transpose()?;
+      let layout = if link.is_some() {
+         item.layout.or(cascade.link_layout(dir))
+      } else {
+         item.layout
+      };
+
       let metadata = Metadata {
+         slug: match (&permalink, &title, item.date) {
+            (None, None, Some(date)) => Slug::for_note(date, &source.path)?,
+            _ => Slug::new(permalink.as_deref(), &source.path)?,
+         },
          title,
-         slug: Slug::new(permalink.as_deref(), &source.path)?,
          subtitle: item.subtitle.map(render).transpose()?,
-         link: item.link, // I don’t *think* this makes sense to have in the cascade.
-         layout: item
-            .layout
+         link,
+         paginate: item.paginate,
+         layout: layout
             .or(cascade.layout(dir))
             .unwrap_or(default_template_name),
          summary: item.summary.map(render).transpose()?,
@@ -132,39 +166,133 @@ impl Metadata {
             tags.extend(cascade.tags(dir));
             tags
          },
+         taxonomies: taxonomies
+            .iter()
+            .filter_map(|taxonomy| {
+               item.taxonomies.get(&taxonomy.name).map(|value| {
+                  taxonomy
+                     .terms_from(value)
+                     .map(|terms| (taxonomy.name.clone(), terms))
+               })
+            })
+            .collect::<Result<_, _>>()?,
+         draft: item.draft,
          featured: item.featured,
          image: item.image.or(cascade.image(dir)).map(Image::from),
          book: item.book.or(cascade.book(dir)).map(Book::from),
-         series: item.series.or(cascade.series(dir)),
+         series: match (item.series, cascade.series(dir)) {
+            (Some(from_item), from_cascade) => Some(serial::Series {
+               name: from_item
+                  .name
+                  .or(from_cascade.and_then(|series| series.name)),
+               part: from_item.part,
+            }),
+            (None, from_cascade) => from_cascade,
+         },
          subscribe: cascade.subscribe(dir),
          work,
       };
 
       Ok((metadata, item.date))
    }
+
+   /// Notes are short posts without titles, like those on microblogging services.
+   pub fn is_note(&self) -> bool {
+      self.title.is_none()
+   }
+}
+
+/// Every key in the front matter which is not one of the item fields is collected as a
+/// possible taxonomy, so anything not configured as one is likely a mistake, e.g. a
+/// typo. Existing content may use such keys on purpose, though, so they are only worth
+/// a warning.
+fn unknown_fields<'e>(
+   extra: &'e HashMap<String, serde_yaml::Value>,
+   taxonomies: &[Taxonomy],
+) -> Vec<&'e str> {
+   let mut unknown = extra
+      .keys()
+      .filter(|key| !taxonomies.iter().any(|taxonomy| &taxonomy.name == *key))
+      .map(String::as_str)
+      .collect::<Vec<_>>();
+
+   unknown.sort_unstable();
+   unknown
 }
 
+/// A Markdown field, rendered both as HTML and as plain text.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Rendered {
-   source: String,
    html: String,
+   plain: String,
 }
 
 impl Rendered {
    fn markdown(src: &str, md: &Markdown) -> Result<Rendered, Error> {
       md.render(src, |s| Ok(s.to_string()))
          .map(|(_, rendered)| Rendered {
-            source: src.to_owned(),
             html: rendered.html().to_string(),
+            plain: rendered.plain().to_string(),
          })
          .map_err(Error::from)
    }
 
-   pub fn plain(&self) -> String {
-      // TODO: at construction above, create a plain text version as well as an HTML
-      // version of the text.
-      self.source.clone()
+   pub fn html(&self) -> &str {
+      &self.html
+   }
+
+   /// The text without any HTML or Markdown syntax, e.g. for meta descriptions.
+   pub fn plain(&self) -> &str {
+      &self.plain
+   }
+}
+
+/// The target of a link item, i.e. the post elsewhere which the item links to.
+#[derive(Debug, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub struct Link {
+   pub url: String,
+   /// The domain of the URL, without any leading `www.`, e.g. `example.com`.
+   pub domain: String,
+   pub title: Option<String>,
+   pub author: Option<String>,
+}
+
+impl TryFrom<serial::Link> for Link {
+   type Error = FieldError;
+
+   fn try_from(value: serial::Link) -> Result<Self, Self::Error> {
+      let (url, title, author) = match value {
+         serial::Link::Url(url) => (url, None, None),
+         serial::Link::Detailed { url, title, author } => (url, title, author),
+      };
+
+      let domain = domain_of(&url)
+         .ok_or_else(|| FieldError::Link(url.clone()))?
+         .to_owned();
+
+      Ok(Link {
+         url,
+         domain,
+         title,
+         author: author.map(|a| a.to_string()),
+      })
+   }
+}
+
+/// The domain of an absolute URL, e.g. `example.com` for `https://www.example.com/a/b`.
+fn domain_of(url: &str) -> Option<&str> {
+   let (scheme, rest) = url.split_once("://")?;
+   if scheme.is_empty() {
+      return None;
    }
+
+   let authority = rest.split(['/', '?', '#']).next()?;
+   let host = authority
+      .rsplit_once('@')
+      .map_or(authority, |(_, host)| host);
+   let host = host.split(':').next()?;
+   let host = host.strip_prefix("www.").unwrap_or(host);
+   (!host.is_empty()).then_some(host)
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -188,7 +316,7 @@ impl Slug {
    /// # Errors
    ///
    /// This function will return an error if .
-   fn new(permalink: Option<&str>, source: &Utf8Path) -> Result<Slug, Error> {
+   pub fn new(permalink: Option<&str>, source: &Utf8Path) -> Result<Slug, Error> {
       match permalink {
          Some(s) => Ok(Slug::Permalink(s.to_owned())),
 
@@ -210,8 +338,30 @@ impl Slug {
          }
       }
    }
+
+   /// Build the slug for a note, which has no title, from its date instead of its file
+   /// name, e.g. `notes/2025-06-22-091542` for `notes/whatever.md`, so that it does not
+   /// change if the file is renamed. This is the same as the file name `lx create`
+   /// gives notes.
+   pub fn for_note(
+      date: DateTime<FixedOffset>,
+      source: &Utf8Path,
+   ) -> Result<Slug, Error> {
+      let start = source.parent().ok_or_else(|| Error::BadPermalink {
+         reason: format!("missing parent on '{source}'?!?"),
+         source: None,
+      })?;
+
+      Ok(Slug::FromPath(
+         start.join(date.format(NOTE_SLUG_FORMAT).to_string()),
+      ))
+   }
 }
 
+/// The format of the date-based slugs for notes. It goes down to the second, since
+/// several notes can easily be written in the same minute.
+pub const NOTE_SLUG_FORMAT: &str = "%Y-%m-%d-%H%M%S";
+
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Qualifiers {
    pub audience: Option<String>,
@@ -241,31 +391,30 @@ impl Component for Qualifiers {
    }
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Book {
-   title: Option<String>,
-   author: Option<String>,
+   pub title: Option<String>,
+   pub author: Option<String>,
    /// Year is a `String`, rather than something like a `u16`, because years
    /// are a lot more complicated than a number represents. If I write "400
    /// B.C.", for example, the system should still work.
-   year: Option<String>,
-   editors: Option<String>,
-   translators: Option<String>,
-   cover: Option<Image>,
-   link: Option<String>,
+   pub year: Option<String>,
+   pub editors: Option<String>,
+   pub translators: Option<String>,
+   pub cover: Option<Image>,
+   pub link: Option<String>,
    pub review: Option<serial::Review>,
 }
 
 impl Book {
-   fn as_view<'a, I: IntoIterator<Item = &'a Item<'a>>>(
+   /// The view for this book, with an archive of the given items, which should be the
+   /// items about it.
+   pub fn as_view<'a, I: IntoIterator<Item = &'a Item<'a>>>(
       &'a self,
       items: I,
-   ) -> Result<BookView<'a>, Box<dyn std::error::Error>> {
-      let archive = Archive::new(
-         items
-            .into_iter()
-            .filter(|item| item.data().book.as_ref().is_some_and(|book| book == self)),
-      )?;
+      order: archive::Order,
+   ) -> Result<BookView<'a>, archive::Error> {
+      let archive = Archive::new(items, order)?;
 
       Ok(BookView {
          book: self,
@@ -301,9 +450,9 @@ impl From<serial::Book> for Book {
 }
 
 #[derive(Debug, Serialize)]
-struct BookView<'a> {
-   book: &'a Book,
-   archive: Archive<'a>,
+pub struct BookView<'a> {
+   pub book: &'a Book,
+   pub archive: Archive<'a>,
 }
 
 impl<'a> Component for BookView<'a> {
@@ -521,6 +670,12 @@ pub enum Error {
       #[from]
       source: lx_md::Error,
    },
+
+   #[error(transparent)]
+   Taxonomy {
+      #[from]
+      source: taxonomy::Error,
+   },
 }
 
 impl Error {
@@ -534,6 +689,9 @@ pub enum FieldError {
    #[error("missing `updated.at` field")]
    Update,
 
+   #[error("`link` must be an absolute URL, but was '{0}'")]
+   Link(String),
+
    #[error("missing `{0}` in {1}")]
    Work(WorkError, WorkMissingFrom),
 }
@@ -652,6 +810,78 @@ mod tests {
       assert_eq!(Slug::new(None, &source).unwrap(), Slug::FromPath(expected));
    }
 
+   #[test]
+   fn link_from_url_or_details() {
+      let link = Link::try_from(serial::Link::Url(String::from(
+         "https://www.example.com:8080/a/post?b=c#d",
+      )))
+      .unwrap();
+      assert_eq!(link.domain, "example.com");
+      assert_eq!(link.title, None);
+
+      let link: serial::Link = serde_yaml::from_str(
+         "url: https://me@blog.example.org\ntitle: A Post\nauthor: [Alex, Sam]\n",
+      )
+      .unwrap();
+      let link = Link::try_from(link).unwrap();
+      assert_eq!(link.domain, "blog.example.org");
+      assert_eq!(link.title.as_deref(), Some("A Post"));
+      assert_eq!(link.author.as_deref(), Some("Alex and Sam"));
+
+      for relative in ["/journal/post", "example.com/post", "https://"] {
+         assert!(matches!(
+            Link::try_from(serial::Link::Url(String::from(relative))),
+            Err(FieldError::Link(_))
+         ));
+      }
+   }
+
+   #[test]
+   fn slug_for_note_from_date() {
+      let date = DateTime::parse_from_rfc3339("2025-06-22T09:15:42-06:00").unwrap();
+      let source = Utf8PathBuf::from("content/notes/Whatever Name.md");
+      let expected = Utf8PathBuf::from("content/notes/2025-06-22-091542");
+
+      assert_eq!(
+         Slug::for_note(date, &source).unwrap(),
+         Slug::FromPath(expected)
+      );
+   }
+
+   #[test]
+   fn rendered_as_html_and_plain_text() {
+      let md = Markdown::new(None);
+      let rendered = Rendered::markdown(
+         "A *really* \"good\" [link](https://example.com)\nwith `code`---and more.\n\n\
+          - one\n- two![alt text](image.png)\n",
+         &md,
+      )
+      .unwrap();
+
+      assert!(rendered.html().contains("<em>really</em>"));
+      assert_eq!(
+         rendered.plain(),
+         "A really “good” link with code—and more.\n\none\ntwo"
+      );
+   }
+
+   #[test]
+   fn unknown_fields_are_reported() {
+      let taxonomies = [Taxonomy {
+         name: String::from("categories"),
+         hierarchical: false,
+         layout: None,
+      }];
+      let extra: HashMap<String, serde_yaml::Value> =
+         serde_yaml::from_str("categories: [a]\ncatgories: [b]\naudience: c\n").unwrap();
+
+      assert!(unknown_fields(&HashMap::new(), &taxonomies).is_empty());
+      assert_eq!(
+         unknown_fields(&extra, &taxonomies),
+         ["audience", "catgories"]
+      );
+   }
+
    #[test]
    fn nice_list_formatting() {
       assert_eq!(
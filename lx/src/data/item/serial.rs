@@ -22,8 +22,16 @@ pub struct Item {
    pub subtitle: Option<String>,
    pub summary: Option<String>,
    pub date: Option<DateTime<FixedOffset>>,
-   /// For link items, the URL to the “target” post.
-   pub link: Option<String>,
+   /// Whether the item is still a draft, and so should not be published.
+   #[serde(default)]
+   pub draft: bool,
+   /// For link items, the “target” post: its URL, or its URL along with its title
+   /// and author.
+   pub link: Option<Link>,
+   /// Whether to split the posts in this item's directory across multiple pages, e.g.
+   /// for an index page. Only meaningful when the site config sets a page size.
+   #[serde(default)]
+   pub paginate: bool,
    /// Relative path to specify a different location from the source location.
    pub permalink: Option<String>,
    pub qualifiers: Option<Qualifiers>,
@@ -42,6 +50,12 @@ pub struct Item {
    pub thanks: Option<String>,
    pub tags: Option<Vec<String>>,
    pub work: Option<MusicalWork>,
+   // --- End section of fields also available in AmbientMetadata --- //
+   /// Everything else, which may include terms for any taxonomies configured for the
+   /// site. These are checked against the config when resolving the metadata, with a
+   /// warning for any which are not taxonomies.
+   #[serde(flatten)]
+   pub taxonomies: HashMap<String, serde_yaml::Value>,
 }
 
 #[derive(Error, Debug)]
@@ -52,8 +66,17 @@ pub struct ItemParseError {
 }
 
 impl Item {
-   pub fn try_parse(src: &str) -> Result<Item, ItemParseError> {
-      serde_yaml::from_str(src).map_err(|e| ItemParseError {
+   /// Parse the metadata in `src`, which comes from the file with `contents`. Errors
+   /// refer to lines and columns in that file, not in the metadata alone.
+   pub fn try_parse(src: &str, contents: &str) -> Result<Item, ItemParseError> {
+      // Padding the metadata with the lines which precede it in the file means that
+      // the lines and columns in YAML errors are already the ones in the file.
+      let lines_before = contents
+         .find(src)
+         .map_or(0, |start| contents[..start].matches('\n').count());
+      let padded = "\n".repeat(lines_before) + src;
+
+      serde_yaml::from_str(&padded).map_err(|e| ItemParseError {
          unparseable: src.to_string(),
          source: e,
       })
@@ -75,6 +98,8 @@ pub struct Ambient {
    pub featured: bool,
    pub image: Option<Image>,
    pub layout: Option<String>,
+   /// The layout for link items, which they use instead of `layout`.
+   pub link_layout: Option<String>,
    pub qualifiers: Option<Qualifiers>,
    pub series: Option<Series>,
    pub subscribe: Option<Subscribe>,
@@ -98,6 +123,17 @@ impl Component for Qualifiers {
    const VIEW_NAME: &'static str = "qualifiers";
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum Link {
+   Url(String),
+   Detailed {
+      url: String,
+      title: Option<String>,
+      author: Option<Authorship>,
+   },
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(untagged)]
 pub enum Video {
@@ -114,8 +150,12 @@ pub struct Retraction {
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct Subscribe {
-   atom: Option<String>,
-   json: Option<String>,
+   /// Where to put the Atom feed for this directory, relative to it.
+   pub atom: Option<String>,
+   /// Where to put the JSON Feed for this directory, relative to it.
+   pub json: Option<String>,
+   /// Where to put a JSON Feed of only the notes in this directory, relative to it.
+   pub notes: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -150,30 +190,18 @@ impl fmt::Display for Authorship {
    }
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct BibleRef {
-   translation: String,
-   book: String,
-   /// This needs to handle the following:
-   ///
-   /// - Individual verse references: "John 1:1"
-   /// - Books without chapters "Jude 1"
-   /// - Within-chapter ranges: "John 1:1–3"
-   /// - Multi-chapter ranges: "John 4–5"
-   /// - Verse ranges crossing chapter breaks: "John 1:35–2:12"
-   ///
-   /// Given all of these, "just use a string" makes far more sense than trying
-   /// to build a complex set of alternative types for it. (This is, after all,
-   /// not a Bible application, where that would be a thing to parse!)
-   passage: String,
-}
-
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct Review {
    rating: Rating,
    summary: String,
 }
 
+impl Review {
+   pub fn rating(&self) -> &Rating {
+      &self.rating
+   }
+}
+
 impl fmt::Display for Review {
    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
       write!(f, "{}: {}", self.rating, self.summary)
@@ -184,8 +212,11 @@ impl fmt::Display for Review {
 // but in fact it should be derived from the same text as its `Display`
 // implementation below. (A later enhancement: converting "****" etc. to it or
 // something cool like that.)
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
-enum Rating {
+//
+// The variants are declared from worst to best, so the derived ordering sorts by how
+// strongly the book is recommended.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Rating {
    #[serde(rename = "Not recommended")]
    NotRecommended,
    #[serde(rename = "Recommended with qualifications")]
@@ -14,3 +14,16 @@ pub fn write_to_fmt(
 
    Ok(())
 }
+
+/// The message for an error and for each of its sources, outermost first.
+pub fn chain(root: &dyn Error) -> Vec<String> {
+   let mut messages = vec![root.to_string()];
+
+   let mut error = root.source();
+   while let Some(nested) = error {
+      messages.push(nested.to_string());
+      error = nested.source();
+   }
+
+   messages
+}
@@ -1,18 +1,154 @@
+mod atom;
 mod json;
 
-use std::convert::TryFrom;
-
-use json_feed::{AuthorOptions, JSONFeed};
+use camino::{Utf8Path, Utf8PathBuf};
 use thiserror::Error;
 
-use crate::page::Post;
-use crate::{
-   data::config::Config,
-   page::PostAndConfig,
+use crate::data::{
+   config::Config,
+   item::{cascade::Cascade, serial::Subscribe},
 };
+use crate::page::{Item, Post};
+use crate::pagination::{self, paginate};
+
+/// The kinds of feed `lx` knows how to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+   Atom,
+   Json,
+}
+
+impl Format {
+   /// Where a data file says to put this kind of feed, if anywhere.
+   fn declared_in(self, subscribe: &Subscribe) -> Option<&str> {
+      match self {
+         Format::Atom => subscribe.atom.as_deref(),
+         Format::Json => subscribe.json.as_deref(),
+      }
+   }
+
+   /// Where a data file says to put a feed of only the notes in its directory, if
+   /// anywhere. Notes feeds are always JSON Feeds, which can carry plain text content
+   /// for syndicating notes elsewhere.
+   fn notes_declared_in(self, subscribe: &Subscribe) -> Option<&str> {
+      match self {
+         Format::Atom => None,
+         Format::Json => subscribe.notes.as_deref(),
+      }
+   }
+
+   /// Where the site-wide feed goes when the content root does not say otherwise, if
+   /// this kind of feed is always emitted for the whole site.
+   fn default_site_path(self) -> Option<&'static str> {
+      match self {
+         Format::Atom => None,
+         Format::Json => Some("feed.json"),
+      }
+   }
+}
+
+/// Build one feed for each directory in the content tree which declares a feed of the
+/// given format in its data file, along with the site-wide feed if there is one, and any
+/// notes feeds declared in the same way.
+pub fn feeds_for<'a>(
+   format: Format,
+   config: &'a Config,
+   items: &'a [Item<'a>],
+   cascade: &Cascade,
+   content_dir: &Utf8Path,
+) -> Result<Vec<Feed<'a>>, Error> {
+   let mut site_path = format.default_site_path().map(Utf8PathBuf::from);
+   let mut feeds = Vec::new();
+
+   for (dir, subscribe) in cascade.subscriptions() {
+      let declared = format.declared_in(subscribe);
+      let notes = format.notes_declared_in(subscribe);
+      if declared.is_none() && notes.is_none() {
+         continue;
+      }
+
+      let home = dir
+         .strip_prefix(content_dir)
+         .map_err(|source| Error::BadDirectory {
+            dir: dir.to_owned(),
+            source,
+         })?;
+
+      let title = match home.file_name() {
+         Some(name) => format!("{}: {name}", config.title),
+         None => config.title.clone(),
+      };
+
+      let posts = move || {
+         posts_in(items).filter(move |post| post.page.source.path.starts_with(dir))
+      };
+
+      if let Some(notes) = notes {
+         feeds.push(Feed::new(
+            format,
+            format!("{title} (notes)"),
+            config,
+            feed_path(home, notes),
+            home.to_owned(),
+            posts().filter(|post| post.page.data.is_note()),
+         ));
+      }
+
+      let Some(declared) = declared else {
+         continue;
+      };
+
+      // The content root is the site-wide feed.
+      if home.as_str().is_empty() {
+         site_path = Some(feed_path(home, declared));
+         continue;
+      }
+
+      feeds.push(Feed::new(
+         format,
+         title,
+         config,
+         feed_path(home, declared),
+         home.to_owned(),
+         posts(),
+      ));
+   }
+
+   if let Some(site_path) = site_path {
+      feeds.push(Feed::new(
+         format,
+         config.title.clone(),
+         config,
+         site_path,
+         Utf8PathBuf::new(),
+         posts_in(items),
+      ));
+   }
+
+   Ok(feeds)
+}
+
+fn posts_in<'a>(items: &'a [Item<'a>]) -> impl Iterator<Item = &'a Post<'a>> {
+   items.iter().filter_map(|item| match item {
+      Item::Post(post) => Some(post),
+      Item::Page(_) => None,
+   })
+}
+
+/// A feed path in a data file is relative to the directory it is declared in, unless
+/// it starts with `/`, in which case it is relative to the root of the site.
+fn feed_path(home: &Utf8Path, declared: &str) -> Utf8PathBuf {
+   match declared.strip_prefix('/') {
+      Some(root_relative) => Utf8PathBuf::from(root_relative),
+      None => home.join(declared),
+   }
+}
 
 /// Required resources for a `Feed`.
 pub struct Feed<'a> {
+   /// Which kind of feed to write.
+   format: Format,
+
    /// Every feed has its own title.
    title: String,
 
@@ -20,51 +156,159 @@ pub struct Feed<'a> {
    /// full set of data specified for Atom, JSON, or RSS.
    site_config: &'a Config,
 
-   /// The set of items to render in the feed. A read-only slice because I will
-   /// never actually need to *write* to these. I just need the parsed metadata
-   /// and rendered HTML contents of the page, to render into the template.
-   items: &'a [Post<'a>],
+   /// Where the feed itself lives, relative to the root of the site.
+   path: Utf8PathBuf,
+
+   /// The directory the feed describes, relative to the root of the site. For the
+   /// site-wide feed, this is empty.
+   home: Utf8PathBuf,
+
+   /// The set of items to render in the feed, newest first. Read-only references
+   /// because I will never actually need to *write* to these. I just need the
+   /// parsed metadata and rendered HTML contents of the page, to render into the
+   /// template.
+   items: Vec<&'a Post<'a>>,
+
+   /// Where the next page of the feed lives, relative to the root of the site, if the
+   /// feed is paginated and this is not the last page.
+   next: Option<Utf8PathBuf>,
 }
 
 impl<'a> Feed<'a> {
-   pub fn _new(title: String, site_config: &'a Config, items: &'a [Post]) -> Feed<'a> {
+   pub fn new(
+      format: Format,
+      title: String,
+      site_config: &'a Config,
+      path: Utf8PathBuf,
+      home: Utf8PathBuf,
+      items: impl IntoIterator<Item = &'a Post<'a>>,
+   ) -> Feed<'a> {
+      let mut items = items.into_iter().collect::<Vec<_>>();
+      items.sort_by(|a, b| b.cmp(a));
+
       Feed {
+         format,
          title,
          site_config,
+         path,
+         home,
          items,
+         next: None,
+      }
+   }
+
+   /// Split the feed into pages, each linking to the next one. The first page stays at
+   /// the feed's own path; later pages go under `page/<n>/` next to it, so page 2 of
+   /// `journal/feed.json` is `journal/page/2/feed.json`.
+   pub fn paginated(self, options: pagination::Options) -> Vec<Feed<'a>> {
+      let file_name = self.path.file_name().unwrap_or_default().to_owned();
+      let base = self.path.parent().unwrap_or(Utf8Path::new("")).to_owned();
+
+      paginate(&self.items, options, &base)
+         .into_iter()
+         .map(|page| Feed {
+            format: self.format,
+            title: self.title.clone(),
+            site_config: self.site_config,
+            path: page.path.join(&file_name),
+            home: self.home.clone(),
+            items: page.items.to_vec(),
+            next: page.next.map(|next| next.join(&file_name)),
+         })
+         .collect()
+   }
+
+   /// Where to write the feed, relative to the output directory.
+   pub fn path(&self) -> &Utf8Path {
+      &self.path
+   }
+
+   /// Render the feed in its own format.
+   pub fn render(&self) -> Result<String, Error> {
+      match self.format {
+         Format::Atom => self.to_atom(),
+         Format::Json => self.to_json(),
       }
    }
+
+   fn feed_url(&self) -> String {
+      url_for(self.site_config, self.path.as_str())
+   }
+
+   fn next_url(&self) -> Option<String> {
+      self
+         .next
+         .as_ref()
+         .map(|next| url_for(self.site_config, next.as_str()))
+   }
+
+   fn home_page_url(&self) -> String {
+      if self.home.as_str().is_empty() {
+         url_for(self.site_config, "")
+      } else {
+         url_for(self.site_config, &format!("{}/", self.home))
+      }
+   }
+}
+
+fn url_for(config: &Config, path: &str) -> String {
+   String::from(config.url.trim_end_matches('/')) + "/" + path.trim_start_matches('/')
 }
 
 #[derive(Error, Debug)]
 pub enum Error {
    #[error("could not convert to JSON feed")]
    Json(String),
-   #[error("could not convert to Atom feed")]
+   #[error("could not serialize JSON feed")]
+   JsonSerialization { source: serde_json::Error },
+   #[error("could not write Atom feed")]
    Atom,
+   #[error("feed directory '{dir}' is not in the content directory")]
+   BadDirectory {
+      dir: Utf8PathBuf,
+      source: std::path::StripPrefixError,
+   },
 }
 
-impl<'a> TryFrom<Feed<'a>> for JSONFeed {
-   type Error = Error;
-
-   fn try_from(feed: Feed<'a>) -> Result<Self, Self::Error> {
-      let items = feed
-         .items
-         .iter()
-         .map(|page| json_feed::FeedItem::from(PostAndConfig(page, feed.site_config)))
-         .collect();
-
-      // TODO: needs the info for the *feed* URL.
-      let feed = JSONFeed::builder(&feed.title, items)
-         .with_author(&AuthorOptions {
-            name: Some(&feed.site_config.author.name),
-            url: None,
-            avatar: None,
-         })
-         .map_err(Error::Json)?
-         .with_description(&feed.site_config.description)
-         .build();
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::test_support;
+
+   #[test]
+   fn feed_path_relative_to_directory() {
+      assert_eq!(
+         feed_path(Utf8Path::new("journal"), "feed.json"),
+         Utf8PathBuf::from("journal/feed.json")
+      );
+   }
+
+   #[test]
+   fn next_url() {
+      let config = test_support::config();
+
+      let mut feed = Feed::new(
+         Format::Json,
+         config.title.clone(),
+         &config,
+         Utf8PathBuf::from("journal/feed.json"),
+         Utf8PathBuf::from("journal"),
+         [],
+      );
+      assert_eq!(feed.next_url(), None);
+
+      feed.next = Some(Utf8PathBuf::from("journal/page/2/feed.json"));
+      assert_eq!(
+         feed.next_url().as_deref(),
+         Some("https://example.com/journal/page/2/feed.json")
+      );
+   }
 
-      Ok(feed)
+   #[test]
+   fn feed_path_relative_to_root() {
+      assert_eq!(
+         feed_path(Utf8Path::new("journal"), "/journal.json"),
+         Utf8PathBuf::from("journal.json")
+      );
    }
 }
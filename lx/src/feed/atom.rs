@@ -0,0 +1,241 @@
+//! An [Atom 1.0](https://www.rfc-editor.org/rfc/rfc4287) writer.
+//!
+//! Atom is simple enough that there is no need to pull in an XML library: every piece
+//! of text goes through [`escape`], and the structure is fixed.
+
+use std::fmt::Write;
+
+use chrono::{DateTime, FixedOffset};
+
+use super::{Error, Feed};
+use crate::page::Post;
+
+const GENERATOR: &str = "lx";
+
+impl Feed<'_> {
+   /// Render the feed as an Atom document.
+   pub(super) fn to_atom(&self) -> Result<String, Error> {
+      let config = self.site_config;
+      let feed_url = self.feed_url();
+
+      let mut xml = String::new();
+      writeln!(xml, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+      writeln!(xml, r#"<feed xmlns="http://www.w3.org/2005/Atom">"#)?;
+      writeln!(xml, "<title>{}</title>", escape(&self.title))?;
+      writeln!(xml, "<subtitle>{}</subtitle>", escape(&config.description))?;
+      writeln!(xml, "<id>{}</id>", escape(&feed_url))?;
+      writeln!(
+         xml,
+         r#"<link rel="self" type="application/atom+xml" href="{}"/>"#,
+         escape(&feed_url)
+      )?;
+      writeln!(
+         xml,
+         r#"<link rel="alternate" type="text/html" href="{}"/>"#,
+         escape(&self.home_page_url())
+      )?;
+      if let Some(next_url) = self.next_url() {
+         writeln!(xml, r#"<link rel="next" href="{}"/>"#, escape(&next_url))?;
+      }
+      writeln!(xml, "<updated>{}</updated>", self.updated().to_rfc3339())?;
+      writeln!(xml, "<author>")?;
+      writeln!(xml, "<name>{}</name>", escape(&config.author.name))?;
+      writeln!(
+         xml,
+         "<email>{}</email>",
+         escape(&config.author.email.to_string())
+      )?;
+      writeln!(xml, "<uri>{}</uri>", escape(&config.url))?;
+      writeln!(xml, "</author>")?;
+      writeln!(xml, "<icon>{}</icon>", escape(config.image.url()))?;
+      writeln!(xml, "<generator>{GENERATOR}</generator>")?;
+
+      for post in &self.items {
+         self.write_entry(&mut xml, post)?;
+      }
+
+      writeln!(xml, "</feed>")?;
+      Ok(xml)
+   }
+
+   fn write_entry(&self, xml: &mut String, post: &Post) -> Result<(), Error> {
+      let data = &post.page.data;
+
+      writeln!(xml, "<entry>")?;
+      // Atom requires a title, but it may be empty, as it is for notes.
+      writeln!(
+         xml,
+         "<title>{}</title>",
+         escape(data.title.as_deref().unwrap_or_default())
+      )?;
+      writeln!(xml, "<id>urn:uuid:{}</id>", post.page.id)?;
+      writeln!(
+         xml,
+         r#"<link rel="alternate" type="text/html" href="{}"/>"#,
+         escape(&post.page.path.url(self.site_config))
+      )?;
+      if let Some(link) = &data.link {
+         let title = link.title.as_deref().unwrap_or(&link.domain);
+         writeln!(
+            xml,
+            r#"<link rel="related" type="text/html" href="{}" title="{}"/>"#,
+            escape(&link.url),
+            escape(title)
+         )?;
+      }
+      writeln!(xml, "<published>{}</published>", post.date.to_rfc3339())?;
+      writeln!(xml, "<updated>{}</updated>", updated(post).to_rfc3339())?;
+      for tag in &data.tags {
+         writeln!(xml, r#"<category term="{}"/>"#, escape(tag))?;
+      }
+      if let Some(summary) = &data.summary {
+         writeln!(
+            xml,
+            r#"<summary type="html">{}</summary>"#,
+            escape(summary.html())
+         )?;
+      }
+      writeln!(
+         xml,
+         r#"<content type="html">{}</content>"#,
+         escape(post.page.content.html())
+      )?;
+      writeln!(xml, "</entry>")?;
+      Ok(())
+   }
+
+   /// The feed as a whole was last updated whenever its most recent entry was. A feed
+   /// with no entries at all has to say *something*, so it uses the Unix epoch, which
+   /// keeps the output the same from one build to the next.
+   fn updated(&self) -> DateTime<FixedOffset> {
+      self
+         .items
+         .iter()
+         .map(|post| updated(post))
+         .max()
+         .unwrap_or(DateTime::UNIX_EPOCH.fixed_offset())
+   }
+}
+
+/// The most recent of the post's publication date and any of its updates.
+fn updated(post: &Post) -> DateTime<FixedOffset> {
+   post
+      .page
+      .data
+      .updated
+      .iter()
+      .map(|update| update.at)
+      .chain(std::iter::once(post.date))
+      .max()
+      .unwrap_or(post.date)
+}
+
+fn escape(text: &str) -> String {
+   let mut escaped = String::with_capacity(text.len());
+   for c in text.chars() {
+      match c {
+         '&' => escaped.push_str("&amp;"),
+         '<' => escaped.push_str("&lt;"),
+         '>' => escaped.push_str("&gt;"),
+         '"' => escaped.push_str("&quot;"),
+         '\'' => escaped.push_str("&apos;"),
+         other => escaped.push(other),
+      }
+   }
+   escaped
+}
+
+impl From<std::fmt::Error> for Error {
+   fn from(_: std::fmt::Error) -> Self {
+      Error::Atom
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use camino::Utf8PathBuf;
+
+   use super::*;
+   use crate::{feed::Format, page::Item, test_support};
+
+   #[test]
+   fn escapes_markup() {
+      assert_eq!(
+         escape(r#"<a href="/x?a=1&b='2'">"#),
+         "&lt;a href=&quot;/x?a=1&amp;b=&apos;2&apos;&quot;&gt;"
+      );
+   }
+
+   #[test]
+   fn empty_feeds_are_stable() {
+      let config = test_support::config();
+
+      let render = || {
+         Feed::new(
+            Format::Atom,
+            config.title.clone(),
+            &config,
+            Utf8PathBuf::from("feed.xml"),
+            Utf8PathBuf::new(),
+            [],
+         )
+         .to_atom()
+         .unwrap()
+      };
+
+      let first = render();
+      assert!(first.contains("<updated>1970-01-01T00:00:00+00:00</updated>"));
+      assert_eq!(first, render());
+   }
+
+   #[test]
+   fn entries_are_ordered_by_instant_and_updated_by_their_latest_update() {
+      let config = test_support::config();
+
+      // As a string, `earlier` sorts after `later`, but it is three hours before it.
+      let post = |name: &str, front_matter: &str| {
+         test_support::source(
+            &format!("{name}.md"),
+            &format!("---\ntitle: {name}\n{front_matter}\n---\n\nText.\n"),
+         )
+      };
+      let sources = [
+         post("earlier", "date: 2025-06-22T10:00:00-02:00"),
+         post(
+            "later",
+            "date: 2025-06-22T09:00:00-06:00\nupdated:\n- at: 2025-07-01T12:00:00Z",
+         ),
+      ];
+
+      let items = sources.iter().map(test_support::item).collect::<Vec<_>>();
+      let posts = items.iter().filter_map(|item| match item {
+         Item::Post(post) => Some(post),
+         Item::Page(_) => None,
+      });
+
+      let atom = Feed::new(
+         Format::Atom,
+         config.title.clone(),
+         &config,
+         Utf8PathBuf::from("feed.xml"),
+         Utf8PathBuf::new(),
+         posts,
+      )
+      .to_atom()
+      .unwrap();
+
+      let later = atom
+         .find("<title>later</title>")
+         .expect("later is in the feed");
+      let earlier = atom
+         .find("<title>earlier</title>")
+         .expect("earlier is in the feed");
+      assert!(later < earlier);
+      assert!(atom.contains("<updated>2025-07-01T12:00:00+00:00</updated>\n<author>"));
+   }
+
+   #[test]
+   fn leaves_plain_text_alone() {
+      assert_eq!(escape("Sym·poly·mathesy"), "Sym·poly·mathesy");
+   }
+}
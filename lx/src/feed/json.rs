@@ -1 +1,46 @@
+use std::convert::TryFrom;
 
+use json_feed::{AuthorOptions, JSONFeed};
+
+use super::{Error, Feed};
+use crate::page::PostAndConfig;
+
+impl Feed<'_> {
+   /// Render the feed as a [JSON Feed](https://jsonfeed.org) document.
+   pub(super) fn to_json(&self) -> Result<String, Error> {
+      let feed = JSONFeed::try_from(self)?;
+      serde_json::to_string(&feed).map_err(|source| Error::JsonSerialization { source })
+   }
+}
+
+impl<'a> TryFrom<&Feed<'a>> for JSONFeed {
+   type Error = Error;
+
+   fn try_from(feed: &Feed<'a>) -> Result<Self, Self::Error> {
+      let config = feed.site_config;
+
+      let items = feed
+         .items
+         .iter()
+         .map(|post| json_feed::FeedItem::from(PostAndConfig(post, config)))
+         .collect();
+
+      let mut builder = JSONFeed::builder(&feed.title, items)
+         .with_author(&AuthorOptions {
+            name: Some(&config.author.name),
+            url: Some(&config.url),
+            avatar: config.author.avatar.as_deref(),
+         })
+         .map_err(Error::Json)?
+         .with_description(&config.description)
+         .with_home_page_url(&feed.home_page_url())
+         .with_feed_url(&feed.feed_url())
+         .with_icon(config.image.url());
+
+      if let Some(next_url) = feed.next_url() {
+         builder = builder.with_next_url(&next_url);
+      }
+
+      Ok(builder.build())
+   }
+}
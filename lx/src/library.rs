@@ -0,0 +1,302 @@
+//! The library: every distinct [`Book`] on the site gets its own page, with an
+//! [`Archive`](crate::archive::Archive) of the posts about it, and the library index
+//! lists them all.
+
+use std::{
+   cmp::Ordering,
+   collections::{BTreeMap, btree_map},
+   fmt::Debug,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use log::warn;
+use serde::Serialize;
+use slug::slugify;
+use thiserror::Error;
+
+use crate::data::item::{Book, serial::Rating};
+use crate::page::Item;
+
+/// Every distinct book referenced by any item on the site.
+pub struct Library {
+   books: Vec<Book>,
+}
+
+impl Library {
+   /// Collect the distinct books from all items. Books are the same if they have the same
+   /// page, i.e. the same title, and posts about the same book fill in each other's data:
+   /// one might have the cover and another the review. Where they both have some field,
+   /// though, it has to be the same. Books without a title cannot be given a page, so
+   /// they are skipped.
+   pub fn new(items: &[Item]) -> Result<Library, Error> {
+      let mut books = BTreeMap::<Utf8PathBuf, Book>::new();
+      for book in items.iter().filter_map(|item| item.data().book.as_ref()) {
+         if book.title.is_none() {
+            warn!("skipping book without a title: {book:?}");
+            continue;
+         }
+
+         match books.entry(path_for(book)) {
+            btree_map::Entry::Vacant(entry) => {
+               entry.insert(book.clone());
+            }
+            btree_map::Entry::Occupied(mut entry) => merge(entry.get_mut(), book)?,
+         }
+      }
+
+      Ok(Library {
+         books: books.into_values().collect(),
+      })
+   }
+
+   pub fn books(&self) -> impl Iterator<Item = &Book> {
+      self.books.iter()
+   }
+
+   pub fn is_empty(&self) -> bool {
+      self.books.is_empty()
+   }
+
+   /// The data for the library index: every book, in each of the orders the index can
+   /// be sorted in.
+   pub fn index(&self) -> Index<'_> {
+      let entries = self.books.iter().map(Entry::from);
+
+      let mut by_title = entries.collect::<Vec<_>>();
+      by_title.sort_by_key(|entry| entry.sort_title());
+
+      let mut by_author = by_title.clone();
+      by_author.sort_by(|a, b| {
+         compare_authors(a.book.author.as_deref(), b.book.author.as_deref())
+      });
+
+      let mut by_year = by_title.clone();
+      by_year
+         .sort_by(|a, b| compare_years(a.book.year.as_deref(), b.book.year.as_deref()));
+
+      // Best first; books without a review at all go last.
+      let mut by_rating = by_title.clone();
+      by_rating.sort_by(|a, b| b.rating.cmp(&a.rating));
+
+      Index {
+         by_title,
+         by_author,
+         by_year,
+         by_rating,
+      }
+   }
+}
+
+/// Fill in whatever `book` is missing from `other`, which has the same page.
+fn merge(book: &mut Book, other: &Book) -> Result<(), Error> {
+   let path = path_for(book);
+   fill(&path, "title", &mut book.title, &other.title)?;
+   fill(&path, "author", &mut book.author, &other.author)?;
+   fill(&path, "year", &mut book.year, &other.year)?;
+   fill(&path, "editors", &mut book.editors, &other.editors)?;
+   fill(
+      &path,
+      "translators",
+      &mut book.translators,
+      &other.translators,
+   )?;
+   fill(&path, "cover", &mut book.cover, &other.cover)?;
+   fill(&path, "link", &mut book.link, &other.link)?;
+   fill(&path, "review", &mut book.review, &other.review)
+}
+
+fn fill<T>(
+   path: &Utf8Path,
+   field: &'static str,
+   value: &mut Option<T>,
+   other: &Option<T>,
+) -> Result<(), Error>
+where
+   T: Clone + PartialEq + Debug,
+{
+   match (value.as_ref(), other) {
+      (Some(first), Some(second)) if first != second => Err(Error::Conflict {
+         path: path.to_owned(),
+         field,
+         first: format!("{first:?}"),
+         second: format!("{second:?}"),
+      }),
+      (None, Some(_)) => {
+         *value = other.clone();
+         Ok(())
+      }
+      _ => Ok(()),
+   }
+}
+
+/// Where a book's page lives, relative to the root of the site.
+pub fn path_for(book: &Book) -> Utf8PathBuf {
+   Utf8PathBuf::from("library").join(slugify(book.title.as_deref().unwrap_or_default()))
+}
+
+/// The library index, with the books sorted each of the ways a reader can browse them.
+#[derive(Debug, Serialize)]
+pub struct Index<'b> {
+   pub by_title: Vec<Entry<'b>>,
+   pub by_author: Vec<Entry<'b>>,
+   pub by_year: Vec<Entry<'b>>,
+   pub by_rating: Vec<Entry<'b>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry<'b> {
+   pub book: &'b Book,
+   pub path: Utf8PathBuf,
+   pub rating: Option<&'b Rating>,
+}
+
+impl<'b> Entry<'b> {
+   fn sort_title(&self) -> String {
+      self
+         .book
+         .title
+         .as_deref()
+         .unwrap_or_default()
+         .to_lowercase()
+   }
+}
+
+impl<'b> From<&'b Book> for Entry<'b> {
+   fn from(book: &'b Book) -> Self {
+      Entry {
+         book,
+         path: path_for(book),
+         rating: book.review.as_ref().map(|review| review.rating()),
+      }
+   }
+}
+
+/// Years are free-form text, so compare by whatever year number they contain, treating
+/// B.C. years as negative. Years without a number sort after all the others.
+/// Authors sort case-insensitively, with books without an author last.
+fn compare_authors(a: Option<&str>, b: Option<&str>) -> Ordering {
+   match (a, b) {
+      (Some(a), Some(b)) => a.to_lowercase().cmp(&b.to_lowercase()),
+      (Some(_), None) => Ordering::Less,
+      (None, Some(_)) => Ordering::Greater,
+      (None, None) => Ordering::Equal,
+   }
+}
+
+fn compare_years(a: Option<&str>, b: Option<&str>) -> Ordering {
+   match (a.and_then(year_number), b.and_then(year_number)) {
+      (Some(a), Some(b)) => a.cmp(&b),
+      (Some(_), None) => Ordering::Less,
+      (None, Some(_)) => Ordering::Greater,
+      (None, None) => Ordering::Equal,
+   }
+}
+
+fn year_number(year: &str) -> Option<i64> {
+   let digits = year
+      .chars()
+      .skip_while(|c| !c.is_ascii_digit())
+      .take_while(|c| c.is_ascii_digit())
+      .collect::<String>();
+
+   let number = digits.parse::<i64>().ok()?;
+   let normalized = year.to_uppercase().replace('.', "");
+   if normalized.contains("BC") {
+      Some(-number)
+   } else {
+      Some(number)
+   }
+}
+
+#[derive(Error, Debug)]
+pub enum Error {
+   #[error(
+      "posts about the book at '{path}' disagree about its {field}: {first} vs. {second}"
+   )]
+   Conflict {
+      path: Utf8PathBuf,
+      field: &'static str,
+      first: String,
+      second: String,
+   },
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::data::item::serial;
+
+   fn book(yaml: &str) -> Book {
+      Book::from(serde_yaml::from_str::<serial::Book>(yaml).unwrap())
+   }
+
+   #[test]
+   fn posts_about_the_same_book_fill_in_its_data() {
+      let mut merged = book("{ title: Hamlet, author: William Shakespeare }");
+      let reviewed = book(
+         "{ title: Hamlet, year: '1603', review: { rating: Required, summary: Yes. } }",
+      );
+
+      merge(&mut merged, &reviewed).unwrap();
+
+      assert_eq!(merged.author.as_deref(), Some("William Shakespeare"));
+      assert_eq!(merged.year.as_deref(), Some("1603"));
+      assert_eq!(merged.review, reviewed.review);
+   }
+
+   #[test]
+   fn posts_about_the_same_book_must_agree() {
+      let mut first = book("{ title: Hamlet, author: William Shakespeare }");
+      let second = book("{ title: Hamlet, author: Christopher Marlowe }");
+
+      assert!(matches!(
+         merge(&mut first, &second),
+         Err(Error::Conflict {
+            field: "author",
+            ..
+         })
+      ));
+   }
+
+   #[test]
+   fn books_without_authors_go_last() {
+      let library = Library {
+         books: vec![
+            book("{ title: Beowulf }"),
+            book("{ title: Hamlet, author: William Shakespeare }"),
+            book("{ title: Emma, author: jane austen }"),
+         ],
+      };
+
+      let titles = library
+         .index()
+         .by_author
+         .iter()
+         .map(|entry| entry.book.title.as_deref().unwrap())
+         .collect::<Vec<_>>();
+      assert_eq!(titles, ["Emma", "Hamlet", "Beowulf"]);
+   }
+
+   #[test]
+   fn plain_years() {
+      assert_eq!(year_number("1952"), Some(1952));
+   }
+
+   #[test]
+   fn years_before_christ() {
+      assert_eq!(year_number("400 B.C."), Some(-400));
+      assert_eq!(year_number("c. 400 BCE"), Some(-400));
+   }
+
+   #[test]
+   fn years_without_numbers_go_last() {
+      assert_eq!(
+         compare_years(Some("unknown"), Some("1952")),
+         Ordering::Greater
+      );
+      assert_eq!(
+         compare_years(Some("400 B.C."), Some("1952")),
+         Ordering::Less
+      );
+   }
+}
@@ -0,0 +1,405 @@
+//! Checking the links in a built site: every internal `href` and `src` must point at a
+//! file in the output, and every `#fragment` at an element with that `id` (or `name`) in
+//! the page it targets. External URLs are never fetched, only collected.
+
+use std::{
+   borrow::Cow,
+   collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+   fmt,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use percent_encoding::percent_decode_str;
+use serde::{Deserialize, Serialize};
+
+/// How to check links, as set by `links` in the site config.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Options {
+   /// Fail `lx publish` if there are any broken internal links, instead of only warning
+   /// about them.
+   #[serde(default)]
+   pub strict: bool,
+
+   /// Where to write a report of every external URL the site links to, relative to the
+   /// site directory. No report is written if this is not set.
+   pub external_report: Option<Utf8PathBuf>,
+}
+
+/// Collects the output of a build, to check the links in it.
+pub struct Checker<'s> {
+   /// The site's own URL, so that absolute links to it can be checked like internal ones.
+   site_url: &'s str,
+   files: HashSet<Utf8PathBuf>,
+   pages: HashMap<Utf8PathBuf, Page>,
+}
+
+#[derive(Debug, Default)]
+struct Page {
+   anchors: HashSet<String>,
+   links: Vec<String>,
+}
+
+/// The result of checking the links in a site.
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+   /// Every broken internal link, ordered by the page it appears on.
+   pub broken: Vec<Broken>,
+
+   /// Every external URL, along with the pages which link to it.
+   pub external: BTreeMap<String, BTreeSet<Utf8PathBuf>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Broken {
+   /// The page with the link, relative to the output directory.
+   pub page: Utf8PathBuf,
+
+   /// The link as it appears in the page.
+   pub href: String,
+
+   pub problem: Problem,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Problem {
+   /// Nothing in the output matches the link.
+   MissingTarget,
+
+   /// The target exists, but has no element with the fragment as its `id`.
+   MissingAnchor { target: Utf8PathBuf },
+}
+
+impl<'s> Checker<'s> {
+   pub fn new(site_url: &'s str) -> Checker<'s> {
+      Checker {
+         site_url: site_url.trim_end_matches('/'),
+         files: HashSet::new(),
+         pages: HashMap::new(),
+      }
+   }
+
+   /// Add an output file which only needs to exist, by its path relative to the output
+   /// directory.
+   pub fn add_file(&mut self, path: Utf8PathBuf) {
+      self.files.insert(path);
+   }
+
+   /// Add an HTML page, by its path relative to the output directory, to check the
+   /// links in it and the anchors it provides.
+   pub fn add_page(&mut self, path: Utf8PathBuf, html: &str) {
+      self.pages.insert(path.clone(), Page::parse(html));
+      self.files.insert(path);
+   }
+
+   pub fn check(&self) -> Report {
+      let mut report = Report::default();
+
+      let mut pages = self.pages.iter().collect::<Vec<_>>();
+      pages.sort_by_key(|(path, _)| *path);
+
+      for (path, page) in pages {
+         for href in &page.links {
+            let problem = match Link::classify(href, self.site_url) {
+               Link::Ignored => None,
+               Link::External(url) => {
+                  report
+                     .external
+                     .entry(url.to_owned())
+                     .or_default()
+                     .insert(path.clone());
+                  None
+               }
+               Link::Internal {
+                  path: target,
+                  fragment,
+               } => self.problem(path, target, fragment),
+            };
+
+            if let Some(problem) = problem {
+               report.broken.push(Broken {
+                  page: path.clone(),
+                  href: href.clone(),
+                  problem,
+               });
+            }
+         }
+      }
+
+      report
+   }
+
+   fn problem(&self, page: &Utf8Path, target: &str, fragment: &str) -> Option<Problem> {
+      let Some(target) = self.resolve(page, target) else {
+         return Some(Problem::MissingTarget);
+      };
+
+      let fragment = decode(fragment);
+      if fragment.is_empty() || fragment == "top" {
+         return None;
+      }
+
+      // Only HTML has anchors to check; a fragment into e.g. a PDF is left alone.
+      let target_page = self.pages.get(&target)?;
+      if target_page.anchors.contains(fragment.as_ref()) {
+         None
+      } else {
+         Some(Problem::MissingAnchor { target })
+      }
+   }
+
+   /// Find the output file a link points to, if there is one: a file at exactly that
+   /// path, or the `index.html` of a directory at that path.
+   fn resolve(&self, page: &Utf8Path, target: &str) -> Option<Utf8PathBuf> {
+      if target.is_empty() {
+         return Some(page.to_owned());
+      }
+
+      let base = if target.starts_with('/') {
+         Utf8Path::new("")
+      } else {
+         page.parent().unwrap_or(Utf8Path::new(""))
+      };
+
+      let mut segments = base.iter().collect::<Vec<_>>();
+      let decoded = decode(target);
+      for segment in decoded.split('/') {
+         match segment {
+            "" | "." => {}
+            ".." => {
+               segments.pop()?;
+            }
+            segment => segments.push(segment),
+         }
+      }
+
+      let path = segments.into_iter().collect::<Utf8PathBuf>();
+      let index = path.join("index.html");
+      if target.ends_with('/') {
+         self.files.contains(&index).then_some(index)
+      } else if self.files.contains(&path) {
+         Some(path)
+      } else {
+         self.files.contains(&index).then_some(index)
+      }
+   }
+}
+
+impl Page {
+   fn parse(html: &str) -> Page {
+      let mut page = Page::default();
+
+      // `tl` is very forgiving, so this only fails on pathological input, which
+      // then simply has nothing to check.
+      let Ok(dom) = tl::parse(html, tl::ParserOptions::default()) else {
+         return page;
+      };
+
+      for tag in dom.nodes().iter().filter_map(tl::Node::as_tag) {
+         let attributes = tag.attributes();
+         if let Some(id) = attributes.id() {
+            page.anchors.insert(id.as_utf8_str().into_owned());
+         }
+
+         if tag.name().as_utf8_str() == "a"
+            && let Some(Some(name)) = attributes.get("name")
+         {
+            page.anchors.insert(name.as_utf8_str().into_owned());
+         }
+
+         for attribute in ["href", "src"] {
+            if let Some(Some(value)) = attributes.get(attribute) {
+               page.links.push(value.as_utf8_str().trim().to_owned());
+            }
+         }
+      }
+
+      page
+   }
+}
+
+enum Link<'a> {
+   /// A link to somewhere in the site, with any query string dropped.
+   Internal {
+      path: &'a str,
+      fragment: &'a str,
+   },
+   External(&'a str),
+   /// Links which cannot be checked at all, like `mailto:` or `data:` URLs.
+   Ignored,
+}
+
+impl<'a> Link<'a> {
+   fn classify(href: &'a str, site_url: &str) -> Link<'a> {
+      let (local, absolute) = match href.strip_prefix(site_url) {
+         Some(rest) if rest.is_empty() || rest.starts_with(['/', '?', '#']) => {
+            (rest, true)
+         }
+         _ if href.starts_with("//") => return Link::External(href),
+         _ => match scheme(href) {
+            Some("http" | "https") => return Link::External(href),
+            Some(_) => return Link::Ignored,
+            None => (href, false),
+         },
+      };
+
+      let (rest, fragment) = local.split_once('#').unwrap_or((local, ""));
+      let path = rest.split_once('?').map_or(rest, |(path, _)| path);
+
+      // The site's own URL with no path is its root, not the current page.
+      let path = if absolute && path.is_empty() {
+         "/"
+      } else {
+         path
+      };
+      Link::Internal { path, fragment }
+   }
+}
+
+/// The scheme of an absolute URL, if `href` is one.
+fn scheme(href: &str) -> Option<&str> {
+   let end = href.find(':')?;
+   let scheme = &href[..end];
+   let mut chars = scheme.chars();
+   let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+      && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+   valid.then_some(scheme)
+}
+
+/// Whether the file at `path` is an HTML page, whose links should be checked.
+pub fn is_page(path: &Utf8Path) -> bool {
+   matches!(path.extension(), Some("html" | "htm"))
+}
+
+fn decode(text: &str) -> Cow<'_, str> {
+   percent_decode_str(text).decode_utf8_lossy()
+}
+
+/// The broken links which fail a build in strict mode.
+#[derive(Debug, thiserror::Error)]
+pub struct Error {
+   pub broken: Vec<Broken>,
+}
+
+impl fmt::Display for Error {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      writeln!(f, "found {} broken links", self.broken.len())?;
+      for broken in &self.broken {
+         writeln!(f, "\t{broken}")?;
+      }
+
+      Ok(())
+   }
+}
+
+impl fmt::Display for Broken {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      match &self.problem {
+         Problem::MissingTarget => {
+            write!(f, "{}: '{}' does not exist", self.page, self.href)
+         }
+         Problem::MissingAnchor { target } => write!(
+            f,
+            "{}: '{}' has no matching anchor in {target}",
+            self.page, self.href
+         ),
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn checker(files: &[(&str, &str)]) -> Report {
+      let mut checker = Checker::new("https://example.com/");
+      for (path, content) in files {
+         let path = Utf8PathBuf::from(*path);
+         if is_page(&path) {
+            checker.add_page(path, content);
+         } else {
+            checker.add_file(path);
+         }
+      }
+      checker.check()
+   }
+
+   fn hrefs(report: &Report) -> Vec<&str> {
+      report.broken.iter().map(|b| b.href.as_str()).collect()
+   }
+
+   #[test]
+   fn resolves_relative_and_root_relative_links() {
+      let report = checker(&[
+         (
+            "journal/post/index.html",
+            r#"<a href="../other/">a</a>
+               <a href="/journal/other">b</a>
+               <a href="https://example.com/style.css?v=1">c</a>
+               <img src="image.png">
+               <a href="/nope/">d</a>
+               <a href="../../../escape">e</a>"#,
+         ),
+         ("journal/other/index.html", ""),
+         ("journal/post/image.png", ""),
+         ("style.css", ""),
+      ]);
+
+      assert_eq!(hrefs(&report), vec!["/nope/", "../../../escape"]);
+      assert!(
+         report
+            .broken
+            .iter()
+            .all(|b| b.problem == Problem::MissingTarget)
+      );
+   }
+
+   #[test]
+   fn checks_fragments_including_footnotes() {
+      let report = checker(&[
+         (
+            "index.html",
+            r##"<p>Text<sup><a href="#fn1" id="fnref1">1</a></sup></p>
+               <a href="#fnref1">back</a>
+               <a href="#missing">x</a>
+               <a href="/about/#team">y</a>
+               <a href="/about/#nobody">z</a>
+               <a name="old-style"></a><a href="#old-style">w</a>
+               <ol><li id="fn1">Note.</li></ol>"##,
+         ),
+         ("about/index.html", r#"<h2 id="team">Team</h2>"#),
+      ]);
+
+      assert_eq!(hrefs(&report), vec!["#missing", "/about/#nobody"]);
+      assert_eq!(
+         report.broken[1].problem,
+         Problem::MissingAnchor {
+            target: Utf8PathBuf::from("about/index.html")
+         }
+      );
+   }
+
+   #[test]
+   fn collects_external_links_and_ignores_other_schemes() {
+      let report = checker(&[
+         (
+            "index.html",
+            r#"<a href="https://rust-lang.org">a</a>
+               <a href="mailto:hello@example.com">b</a>
+               <img src="data:image/png;base64,AAAA">
+               <script src="//cdn.example.org/x.js"></script>"#,
+         ),
+         (
+            "about/index.html",
+            r#"<a href="https://rust-lang.org">a</a>"#,
+         ),
+      ]);
+
+      assert!(report.broken.is_empty());
+      assert_eq!(
+         report.external.keys().collect::<Vec<_>>(),
+         vec!["//cdn.example.org/x.js", "https://rust-lang.org"]
+      );
+      assert_eq!(report.external["https://rust-lang.org"].len(), 2);
+   }
+}
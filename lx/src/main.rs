@@ -5,6 +5,7 @@ use std::io::{BufReader, Read, Write};
 
 use anyhow::anyhow;
 use camino::Utf8PathBuf;
+use chrono::{DateTime, FixedOffset, Local};
 use clap::{Args, CommandFactory, Parser, Subcommand};
 use clap_complete::{generate_to, shells::Fish};
 use log::info;
@@ -12,30 +13,39 @@ use simplelog::{
    ColorChoice, Config, ConfigBuilder, LevelFilter, TermLogger, TerminalMode,
 };
 use syntect::highlighting::ThemeSet;
-use syntect::html::{css_for_theme_with_class_style, ClassStyle};
+use syntect::html::{ClassStyle, css_for_theme_with_class_style};
 use thiserror::Error;
 
 mod archive;
 mod build;
+mod cache;
 mod canonicalized;
-mod collection;
+mod create;
 mod data;
 mod error;
 mod feed;
+mod library;
+mod links;
 mod md;
 mod page;
+mod pagination;
+mod publication;
+mod series;
 mod server;
 mod style;
+mod taxonomy;
 mod templates;
+#[cfg(test)]
+mod test_support;
 
-use crate::build::build_in;
+use crate::build::{Failure, build_in, check, config_for};
+use crate::cache::Cache;
+use crate::canonicalized::Canonicalized;
 use crate::server::serve;
 
 fn main() -> Result<(), anyhow::Error> {
    let mut cli = Cli::parse();
 
-   // TODO: configure Miette or similar to print this particularly nicely. Then we can
-   // just return that!
    setup_logger(&cli)?;
 
    let cwd = std::env::current_dir().expect(
@@ -44,8 +54,49 @@ fn main() -> Result<(), anyhow::Error> {
    let cwd = Utf8PathBuf::try_from(cwd)?;
 
    match cli.command {
-      Command::Publish { site_directory } => {
-         let directory = site_directory
+      Command::Publish {
+         site_directory,
+         no_cache,
+         include_drafts,
+         as_of,
+      } => {
+         let directory: Canonicalized = site_directory
+            .unwrap_or_else(|| {
+               info!(
+                  "No directory passed, using current working directory ({cwd}) instead",
+               );
+               cwd
+            })
+            .try_into()?;
+
+         let cache = if no_cache {
+            Cache::disabled()
+         } else {
+            Cache::for_site(directory.as_ref())
+         };
+
+         let publication = publication::Options {
+            include_drafts,
+            ..publication::Options::published(
+               as_of.unwrap_or_else(|| Local::now().fixed_offset()),
+            )
+         };
+
+         if let Err(error) = build_in(directory, &cache, publication) {
+            let failures = error.failures();
+            report(&failures);
+            Err(Error::Failed(failures.len()))?
+         }
+
+         Ok(())
+      }
+
+      Command::Create {
+         kind,
+         title,
+         site_directory,
+      } => {
+         let directory: Canonicalized = site_directory
             .unwrap_or_else(|| {
                info!(
                   "No directory passed, using current working directory ({cwd}) instead",
@@ -54,10 +105,45 @@ fn main() -> Result<(), anyhow::Error> {
             })
             .try_into()?;
 
-         build_in(directory)?;
+         let config = config_for(&directory)?;
+         let now = Local::now().fixed_offset();
+         let created =
+            create::create(directory.as_ref(), &config, &kind, title.as_deref(), now)?;
+
+         info!("created {}", created.path);
+         println!("{}", created.url);
          Ok(())
       }
 
+      Command::Check {
+         site_directory,
+         json,
+      } => {
+         let directory: Canonicalized = site_directory
+            .unwrap_or_else(|| {
+               info!(
+                  "No directory passed, using current working directory ({cwd}) instead",
+               );
+               cwd
+            })
+            .try_into()?;
+
+         let failures = check(&directory);
+         if json {
+            let report = serde_json::to_string_pretty(&failures)
+               .expect("failures always serialize");
+            println!("{report}");
+         } else {
+            report(&failures);
+         }
+
+         if failures.is_empty() {
+            Ok(())
+         } else {
+            Err(Error::Failed(failures.len()))?
+         }
+      }
+
       Command::Develop {
          site_directory,
          port,
@@ -152,6 +238,13 @@ fn main() -> Result<(), anyhow::Error> {
    }
 }
 
+/// Print each failure with the source it came from, when that is known.
+fn report(failures: &[Failure]) {
+   for failure in failures {
+      eprintln!("{:?}", failure.report());
+   }
+}
+
 fn setup_logger(cli: &Cli) -> Result<(), log::SetLoggerError> {
    let level = if cli.verbose {
       LevelFilter::Trace
@@ -268,6 +361,9 @@ enum Error {
    #[error(transparent)]
    SyntectCSS { source: syntect::Error },
 
+   #[error("found {0} problem(s)")]
+   Failed(usize),
+
    #[error("IO (for {target})")]
    Io {
       target: String,
@@ -292,6 +388,42 @@ enum Command {
    Publish {
       /// The root of the site (if different from the current directory).
       site_directory: Option<Utf8PathBuf>,
+
+      /// Ignore the build cache: re-render everything, and do not update the cache.
+      #[arg(long)]
+      no_cache: bool,
+
+      /// Include items marked `draft: true`.
+      #[arg(long)]
+      include_drafts: bool,
+
+      /// Publish the site as of this moment (e.g. `2025-06-22T09:15:00-06:00`) instead of
+      /// now: items dated after it are left out.
+      #[arg(long)]
+      as_of: Option<DateTime<FixedOffset>>,
+   },
+
+   /// Create a new item of one of the content types in the site config
+   Create {
+      /// The kind of content to create, as named in `content_types` in the site config.
+      kind: String,
+
+      /// The title of the new item.
+      title: Option<String>,
+
+      /// The root of the site (if different from the current directory).
+      #[arg(short, long)]
+      site_directory: Option<Utf8PathBuf>,
+   },
+
+   /// Check the site for errors, without writing anything
+   Check {
+      /// The root of the site (if different from the current directory).
+      site_directory: Option<Utf8PathBuf>,
+
+      /// Print the problems as JSON on stdout, for use by other tools.
+      #[arg(long)]
+      json: bool,
    },
 
    /// Build and serve the site for development
@@ -1,8 +1,9 @@
 use crate::{
    data::{
       config::Config,
-      item::{self, Metadata, Slug, cascade::Cascade, serial},
+      item::{self, Link, Metadata, Slug, cascade::Cascade, serial},
    },
+   taxonomy::Taxonomy,
    templates::component::Component,
 };
 use camino::{Utf8Path, Utf8PathBuf};
@@ -10,9 +11,9 @@ use chrono::{DateTime, FixedOffset};
 use json_feed::Author;
 use lx_md::{self, Markdown, RenderError, ToRender};
 use minijinja::{Environment, State, Value, context, value::Object};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 use std::{cmp::Ordering, sync::Arc};
-use std::{collections::HashMap, fmt, hash::Hash, os::unix::prelude::OsStrExt};
+use std::{fmt, hash::Hash, os::unix::prelude::OsStrExt};
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -20,6 +21,7 @@ pub fn prepare<'e>(
    md: &Markdown,
    source: &'e Source,
    cascade: &Cascade,
+   taxonomies: &[Taxonomy],
 ) -> Result<Prepared<'e>, Error> {
    let lx_md::Prepared {
       metadata_src,
@@ -28,12 +30,15 @@ pub fn prepare<'e>(
 
    let (data, date) = metadata_src
       .ok_or(Error::MissingMetadata)
-      .and_then(|src| serial::Item::try_parse(&src).map_err(Error::from))
+      .and_then(|src| {
+         serial::Item::try_parse(&src, &source.contents).map_err(Error::from)
+      })
       .and_then(|item_metadata| {
          Metadata::resolved(
             item_metadata,
             source,
             cascade,
+            taxonomies,
             String::from("base.jinja"), // TODO: not this
             md,
          )
@@ -74,6 +79,7 @@ impl Prepared<'_> {
    }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Rendered {
    content: lx_md::Rendered,
    date: Option<DateTime<FixedOffset>>,
@@ -112,7 +118,8 @@ pub struct Page<'s> {
    /// The fully parsed metadata associated with the page.
    pub data: Metadata,
 
-   /// The fully rendered contents of the page.
+   /// The fully rendered contents of the page. Templates get its HTML.
+   #[serde(serialize_with = "serialize_html")]
    pub content: lx_md::Rendered,
 
    pub source: &'s Source,
@@ -120,6 +127,17 @@ pub struct Page<'s> {
    pub path: RootedPath,
 }
 
+fn serialize_html<S: Serializer>(
+   content: &lx_md::Rendered,
+   serializer: S,
+) -> Result<S::Ok, S::Error> {
+   serializer.serialize_str(content.html())
+}
+
+/// Serializes as the underlying [`Page`], with a `date` field for posts, so that
+/// templates can work with every item the same way.
+#[derive(Serialize)]
+#[serde(untagged)]
 pub enum Item<'s> {
    Page(Page<'s>),
    Post(Post<'s>),
@@ -178,8 +196,8 @@ impl<'s> Item<'s> {
       }
    }
 
-   pub fn title(&self) -> &str {
-      self.data().title.as_ref()
+   pub fn title(&self) -> Option<&str> {
+      self.data().title.as_deref()
    }
 
    pub fn data(&self) -> &Metadata {
@@ -188,6 +206,14 @@ impl<'s> Item<'s> {
          Item::Post(post) => &post.page.data,
       }
    }
+
+   /// Only posts have dates.
+   pub fn date(&self) -> Option<DateTime<FixedOffset>> {
+      match self {
+         Item::Page(_) => None,
+         Item::Post(post) => Some(post.date),
+      }
+   }
 }
 
 // NOTE: the following all assume stable, unique identifiers for pages. The existing
@@ -210,6 +236,7 @@ impl Hash for Page<'_> {
 
 #[derive(Debug, PartialEq, Eq, Hash, Serialize)]
 pub struct Post<'e> {
+   #[serde(flatten)]
    pub page: Page<'e>,
    pub date: DateTime<FixedOffset>,
 }
@@ -229,17 +256,27 @@ impl Ord for Post<'_> {
 #[derive(Debug, Serialize, Hash, PartialEq, Eq)]
 pub struct PostLink<'e> {
    anchor_title: String,
+   /// Notes have no title, so templates need some other way to show them.
+   title: Option<&'e str>,
+   /// For link items, the post they link to, so that archives can link to both it and
+   /// the item's own permalink.
+   link: Option<&'e Link>,
    slug: &'e Slug,
+   path: &'e RootedPath,
 }
 
 impl<'e> From<&'e Post<'e>> for PostLink<'e> {
    fn from(value: &'e Post<'e>) -> Self {
       PostLink {
          anchor_title: match &value.page.data.link {
-            Some(url) => format!("link to {url}"),
+            Some(link) => format!("link to {}", link.url),
+            None if value.page.data.is_note() => String::from("note permalink"),
             None => String::from("post permalink"),
          },
+         title: value.page.data.title.as_deref(),
+         link: value.page.data.link.as_ref(),
          slug: &value.page.data.slug,
+         path: &value.page.path,
       }
    }
 }
@@ -256,6 +293,13 @@ impl Object for PostLink<'_> {
 
 impl<'e> Component for PostLink<'e> {
    const VIEW_NAME: &'static str = "post-link";
+
+   /// The view gets the link as `post_link`, the same as when archive templates include
+   /// it while looping over their links.
+   fn view(&self, env: &Environment) -> Result<String, minijinja::Error> {
+      env.get_template(&Self::template())?
+         .render(context! { post_link => self })
+   }
 }
 
 #[derive(Error, Debug)]
@@ -295,7 +339,7 @@ pub enum Error {
    },
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct RootedPath(Utf8PathBuf);
 
 impl RootedPath {
@@ -335,16 +379,21 @@ impl From<PostAndConfig<'_, '_, '_>> for json_feed::FeedItem {
       json_feed::FeedItem {
          id: post.page.id.to_string(),
          url: Some(post.page.path.url(config)),
-         external_url: None, // TODO: support for page.link etc.
-         title: Some(post.page.data.title.clone()),
-         content_text: None, // TODO: use this for microblogging?
+         external_url: post.page.data.link.as_ref().map(|link| link.url.clone()),
+         title: post.page.data.title.clone(),
+         // Notes are meant to work as plain text, e.g. when syndicated elsewhere.
+         content_text: post
+            .page
+            .data
+            .is_note()
+            .then(|| post.page.content.plain().to_string()),
          content_html: Some(post.page.content.html().to_string()),
          summary: post
             .page
             .data
             .summary
             .as_ref()
-            .map(|summary| summary.plain()),
+            .map(|summary| summary.plain().to_string()),
          image: post
             .page
             .data
@@ -359,17 +408,19 @@ impl From<PostAndConfig<'_, '_, '_>> for json_feed::FeedItem {
             .updated
             .last()
             .map(|update| update.at.to_rfc3339()),
-         author: Some(Author::All {
-            avatar: "https://cdn.chriskrycho.com/images/avatars/2024%20600%C3%97600.jpeg"
-               .to_string(),
-            name: "Chris Krycho".to_string(),
-            url: "https://www.chriskrycho.com".to_string(),
+         author: Some(match &config.author.avatar {
+            Some(avatar) => Author::All {
+               avatar: avatar.clone(),
+               name: config.author.name.clone(),
+               url: config.url.clone(),
+            },
+            None => Author::NameAndUrl {
+               name: config.author.name.clone(),
+               url: config.url.clone(),
+            },
          }),
          tags: Some(post.page.data.tags.clone()),
          attachments: None,
       }
    }
 }
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Collections(HashMap<Id, crate::collection::Id>);
@@ -0,0 +1,120 @@
+//! Splitting long lists into pages: the first page lives at the list's own path, and
+//! every later page lives under it at `page/<n>/`, e.g. `/tags/rust/page/2/`.
+
+use std::num::NonZeroUsize;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Deserialize, Serialize};
+
+/// How to paginate lists, as set by `pagination` in the site config.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Options {
+   /// How many items to put on each page. Lists are not paginated at all if this is not
+   /// set.
+   pub per_page: Option<NonZeroUsize>,
+}
+
+/// One page of a paginated list, as passed to templates as `pagination`.
+#[derive(Debug, Serialize)]
+pub struct Pagination<'a, T> {
+   /// Which page this is, starting from 1.
+   pub number: usize,
+
+   /// How many pages there are in all.
+   pub total: usize,
+
+   /// Where this page lives, relative to the root of the site.
+   pub path: Utf8PathBuf,
+
+   /// Where the previous page lives, if there is one.
+   pub prev: Option<Utf8PathBuf>,
+
+   /// Where the next page lives, if there is one.
+   pub next: Option<Utf8PathBuf>,
+
+   /// The items on this page.
+   pub items: &'a [T],
+}
+
+/// Split `items` into pages, with the first page at `base`. There is always at least one
+/// page, even when there are no items, so that the list itself still gets rendered.
+pub fn paginate<'a, T>(
+   items: &'a [T],
+   options: Options,
+   base: &Utf8Path,
+) -> Vec<Pagination<'a, T>> {
+   let chunks = match options.per_page {
+      Some(per_page) if !items.is_empty() => items.chunks(per_page.get()).collect(),
+      _ => vec![items],
+   };
+
+   let total = chunks.len();
+   chunks
+      .into_iter()
+      .enumerate()
+      .map(|(index, items)| {
+         let number = index + 1;
+         Pagination {
+            number,
+            total,
+            path: page_path(base, number),
+            prev: (number > 1).then(|| page_path(base, number - 1)),
+            next: (number < total).then(|| page_path(base, number + 1)),
+            items,
+         }
+      })
+      .collect()
+}
+
+/// Where page `number` of a list at `base` lives.
+pub fn page_path(base: &Utf8Path, number: usize) -> Utf8PathBuf {
+   if number <= 1 {
+      base.to_owned()
+   } else {
+      base.join("page").join(number.to_string())
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn per_page(n: usize) -> Options {
+      Options {
+         per_page: NonZeroUsize::new(n),
+      }
+   }
+
+   #[test]
+   fn unpaginated() {
+      let pages = paginate(&[1, 2, 3], Options::default(), Utf8Path::new("tags/rust"));
+      assert_eq!(pages.len(), 1);
+      assert_eq!(pages[0].items, &[1, 2, 3]);
+      assert_eq!(pages[0].prev, None);
+      assert_eq!(pages[0].next, None);
+   }
+
+   #[test]
+   fn empty() {
+      let pages = paginate::<u8>(&[], per_page(2), Utf8Path::new(""));
+      assert_eq!(pages.len(), 1);
+      assert_eq!(pages[0].total, 1);
+   }
+
+   #[test]
+   fn paginated() {
+      let pages = paginate(&[1, 2, 3, 4, 5], per_page(2), Utf8Path::new("tags/rust"));
+      assert_eq!(pages.len(), 3);
+
+      assert_eq!(pages[0].path, Utf8PathBuf::from("tags/rust"));
+      assert_eq!(pages[0].next, Some(Utf8PathBuf::from("tags/rust/page/2")));
+
+      assert_eq!(pages[1].items, &[3, 4]);
+      assert_eq!(pages[1].prev, Some(Utf8PathBuf::from("tags/rust")));
+      assert_eq!(pages[1].next, Some(Utf8PathBuf::from("tags/rust/page/3")));
+
+      assert_eq!(pages[2].items, &[5]);
+      assert_eq!(pages[2].total, 3);
+      assert_eq!(pages[2].next, None);
+   }
+}
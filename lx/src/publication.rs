@@ -0,0 +1,126 @@
+//! Whether an item is published yet. Drafts and items dated in the future are not, so
+//! `lx publish` leaves them out, while `lx develop` includes them, marked as such, to
+//! make it possible to preview them.
+
+use chrono::{DateTime, FixedOffset};
+use serde::Serialize;
+
+use crate::page::Item;
+
+/// Why an item is not published yet, as passed to templates as `unpublished`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Unpublished {
+   /// The item is marked `draft: true`.
+   Draft,
+
+   /// The item is dated after the moment the site is built for.
+   Scheduled,
+}
+
+/// Which unpublished items a build includes.
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+   /// The moment to build the site for: items dated after it are scheduled.
+   pub as_of: DateTime<FixedOffset>,
+   pub include_drafts: bool,
+   pub include_scheduled: bool,
+}
+
+impl Options {
+   /// Only what is published as of the given moment.
+   pub fn published(as_of: DateTime<FixedOffset>) -> Options {
+      Options {
+         as_of,
+         include_drafts: false,
+         include_scheduled: false,
+      }
+   }
+
+   /// Every item, published or not.
+   pub fn everything(as_of: DateTime<FixedOffset>) -> Options {
+      Options {
+         as_of,
+         include_drafts: true,
+         include_scheduled: true,
+      }
+   }
+
+   /// Why `item` is not published yet, if it is not.
+   pub fn unpublished(&self, item: &Item) -> Option<Unpublished> {
+      self.status(item.data().draft, item.date())
+   }
+
+   pub fn includes(&self, item: &Item) -> bool {
+      self.allows(self.unpublished(item))
+   }
+
+   fn status(
+      &self,
+      draft: bool,
+      date: Option<DateTime<FixedOffset>>,
+   ) -> Option<Unpublished> {
+      if draft {
+         Some(Unpublished::Draft)
+      } else if date.is_some_and(|date| date > self.as_of) {
+         Some(Unpublished::Scheduled)
+      } else {
+         None
+      }
+   }
+
+   fn allows(&self, unpublished: Option<Unpublished>) -> bool {
+      match unpublished {
+         None => true,
+         Some(Unpublished::Draft) => self.include_drafts,
+         Some(Unpublished::Scheduled) => self.include_scheduled,
+      }
+   }
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn at(date: &str) -> DateTime<FixedOffset> {
+      DateTime::parse_from_rfc3339(date).unwrap()
+   }
+
+   #[test]
+   fn drafts_and_future_dates_are_unpublished() {
+      let options = Options::published(at("2025-06-22T09:15:00-06:00"));
+
+      assert_eq!(options.status(false, None), None);
+      assert_eq!(
+         options.status(false, Some(at("2025-06-22T09:15:00-06:00"))),
+         None
+      );
+      assert_eq!(
+         options.status(false, Some(at("2025-06-22T16:00:00+00:00"))),
+         Some(Unpublished::Scheduled)
+      );
+      assert_eq!(
+         options.status(true, Some(at("2020-01-01T00:00:00Z"))),
+         Some(Unpublished::Draft)
+      );
+   }
+
+   #[test]
+   fn includes_only_what_the_options_allow() {
+      let now = at("2025-06-22T09:15:00-06:00");
+      let drafts = Options {
+         include_drafts: true,
+         ..Options::published(now)
+      };
+
+      for (options, draft, scheduled) in [
+         (Options::published(now), false, false),
+         (drafts, true, false),
+         (Options::everything(now), true, true),
+      ] {
+         assert!(options.allows(None));
+         assert_eq!(options.allows(Some(Unpublished::Draft)), draft);
+         assert_eq!(options.allows(Some(Unpublished::Scheduled)), scheduled);
+      }
+   }
+}
@@ -0,0 +1,254 @@
+//! Multi-part series: every item which shares a `series.name`, ordered by its `part`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::page::{Item, RootedPath};
+
+/// Every series on the site, keyed by name.
+pub struct Index<'i>(BTreeMap<String, Vec<Part<'i>>>);
+
+struct Part<'i> {
+   number: u8,
+   item: &'i Item<'i>,
+}
+
+impl<'i> Index<'i> {
+   /// Group all items with a named `series` by that name, ordered by part. Only the
+   /// given items are in the index, so that e.g. the navigation between published items
+   /// skips over any parts which are not published yet.
+   pub fn new(items: &'i [Item<'i>]) -> Index<'i> {
+      Index(group(items).0)
+   }
+
+   /// The series context for a given item, if it is part of a series.
+   pub fn context_for(&self, item: &Item) -> Option<Context<'_>> {
+      let series = item.data().series.as_ref()?;
+      let name = series.name.as_deref()?;
+      let (name, parts) = self.0.get_key_value(name)?;
+      let index = parts.iter().position(|part| part.number == series.part)?;
+
+      Some(Context {
+         name,
+         part: series.part,
+         previous: index.checked_sub(1).map(|index| Entry::from(&parts[index])),
+         next: parts.get(index + 1).map(Entry::from),
+         items: parts.iter().map(Entry::from).collect(),
+      })
+   }
+}
+
+/// Check that every item in a series names it, and that each series has exactly one item
+/// for each part, from 1 through the last part.
+pub fn check(items: &[Item]) -> Result<(), Error> {
+   let (by_name, mut problems) = group(items);
+   for (name, parts) in &by_name {
+      let numbered = parts
+         .iter()
+         .map(|part| (part.number, part.item.source().path.as_path()))
+         .collect::<Vec<_>>();
+      problems.extend(check_parts(name, &numbered));
+   }
+
+   if problems.is_empty() {
+      Ok(())
+   } else {
+      Err(Error { problems })
+   }
+}
+
+/// Group the items in each series by its name, sorted by part, along with any items
+/// which are in a series without a name.
+fn group<'i>(items: &'i [Item<'i>]) -> (BTreeMap<String, Vec<Part<'i>>>, Vec<Problem>) {
+   let mut problems = Vec::new();
+   let mut by_name = BTreeMap::<String, Vec<Part<'i>>>::new();
+
+   for item in items {
+      let Some(series) = &item.data().series else {
+         continue;
+      };
+
+      match &series.name {
+         Some(name) => by_name.entry(name.clone()).or_default().push(Part {
+            number: series.part,
+            item,
+         }),
+         None => problems.push(Problem::Unnamed {
+            path: item.source().path.clone(),
+         }),
+      }
+   }
+
+   for parts in by_name.values_mut() {
+      parts.sort_by_key(|part| part.number);
+   }
+
+   (by_name, problems)
+}
+
+/// Check a series' parts, which must already be sorted by part number.
+fn check_parts(name: &str, parts: &[(u8, &Utf8Path)]) -> Vec<Problem> {
+   let mut problems = Vec::new();
+
+   for pair in parts.windows(2) {
+      let [(first, first_path), (second, second_path)] = pair else {
+         unreachable!("`windows(2)` always yields pairs");
+      };
+
+      if first == second {
+         problems.push(Problem::DuplicatePart {
+            series: name.to_owned(),
+            part: *first,
+            paths: (first_path.to_path_buf(), second_path.to_path_buf()),
+         });
+      }
+   }
+
+   let last = parts.last().map(|(number, _)| *number).unwrap_or_default();
+   let missing = (1..=last)
+      .filter(|number| !parts.iter().any(|(part, _)| part == number))
+      .collect::<Vec<_>>();
+
+   if !missing.is_empty() {
+      problems.push(Problem::MissingParts {
+         series: name.to_owned(),
+         parts: missing,
+      });
+   }
+
+   problems
+}
+
+/// The series data available to the template for each of its items.
+#[derive(Debug, Serialize)]
+pub struct Context<'a> {
+   pub name: &'a str,
+   pub part: u8,
+   pub previous: Option<Entry<'a>>,
+   pub next: Option<Entry<'a>>,
+   pub items: Vec<Entry<'a>>,
+}
+
+/// A single item in a series, with just enough data to link to it.
+#[derive(Debug, Serialize)]
+pub struct Entry<'a> {
+   pub part: u8,
+   pub title: Option<&'a str>,
+   pub path: &'a RootedPath,
+}
+
+impl<'a> From<&'a Part<'a>> for Entry<'a> {
+   fn from(part: &'a Part<'a>) -> Self {
+      Entry {
+         part: part.number,
+         title: part.item.title(),
+         path: part.item.path(),
+      }
+   }
+}
+
+// This has its own type definition so that it can report every problem at once, rather
+// than making authors fix them one build at a time.
+#[derive(Error, Debug)]
+pub struct Error {
+   problems: Vec<Problem>,
+}
+
+impl fmt::Display for Error {
+   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      writeln!(f, "invalid series:")?;
+      for problem in &self.problems {
+         writeln!(f, "\t{problem}")?;
+      }
+      Ok(())
+   }
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum Problem {
+   #[error("{path} is part of a series, but the series has no name")]
+   Unnamed { path: Utf8PathBuf },
+
+   #[error("series '{series}' has more than one part {part}: {} and {}", paths.0, paths.1)]
+   DuplicatePart {
+      series: String,
+      part: u8,
+      paths: (Utf8PathBuf, Utf8PathBuf),
+   },
+
+   #[error("series '{series}' is missing parts {parts:?}")]
+   MissingParts { series: String, parts: Vec<u8> },
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::{publication, test_support};
+
+   #[test]
+   fn unpublished_parts_are_skipped() {
+      let part = |number: u8, draft: bool| {
+         test_support::source(
+            &format!("part-{number}.md"),
+            &format!(
+               "---\ntitle: Part {number}\ndate: 2025-06-2{number}T09:00:00Z\n\
+                draft: {draft}\nseries: {{ name: jj, part: {number} }}\n---\n\nText.\n"
+            ),
+         )
+      };
+      let sources = [part(1, false), part(2, true), part(3, false)];
+      let items = sources.iter().map(test_support::item).collect::<Vec<_>>();
+
+      assert!(check(&items).is_ok());
+
+      let as_of = chrono::DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap();
+      let publication = publication::Options::published(as_of);
+      let published = items
+         .into_iter()
+         .filter(|item| publication.includes(item))
+         .collect::<Vec<_>>();
+      let index = Index::new(&published);
+
+      let context = index.context_for(&published[0]).unwrap();
+      assert_eq!(context.items.len(), 2);
+      assert_eq!(context.next.map(|entry| entry.part), Some(3));
+
+      let context = index.context_for(&published[1]).unwrap();
+      assert_eq!(context.previous.map(|entry| entry.part), Some(1));
+   }
+
+   #[test]
+   fn complete_series() {
+      let parts = [(1, Utf8Path::new("a.md")), (2, Utf8Path::new("b.md"))];
+      assert_eq!(check_parts("jj", &parts), vec![]);
+   }
+
+   #[test]
+   fn duplicate_parts() {
+      let parts = [(1, Utf8Path::new("a.md")), (1, Utf8Path::new("b.md"))];
+      assert_eq!(
+         check_parts("jj", &parts),
+         vec![Problem::DuplicatePart {
+            series: String::from("jj"),
+            part: 1,
+            paths: (Utf8PathBuf::from("a.md"), Utf8PathBuf::from("b.md")),
+         }]
+      );
+   }
+
+   #[test]
+   fn missing_parts() {
+      let parts = [(2, Utf8Path::new("b.md")), (4, Utf8Path::new("d.md"))];
+      assert_eq!(
+         check_parts("jj", &parts),
+         vec![Problem::MissingParts {
+            series: String::from("jj"),
+            parts: vec![1, 3],
+         }]
+      );
+   }
+}
@@ -11,11 +11,13 @@ use std::{
 
 use axum::{
    Router,
+   body::Bytes,
    extract::{
       State, WebSocketUpgrade,
       ws::{Message, WebSocket},
    },
-   response::Response,
+   http::{StatusCode, Uri, header},
+   response::{IntoResponse, Redirect, Response},
    routing::{self},
 };
 use camino::{Utf8Path, Utf8PathBuf};
@@ -25,8 +27,9 @@ use futures::{
 };
 use log::{debug, error, info, log_enabled, trace, warn};
 use lx_md::Markdown;
-use notify::RecursiveMode;
+use notify::{EventKind, RecursiveMode};
 use notify_debouncer_full::DebouncedEvent;
+use percent_encoding::percent_decode_str;
 use serde::Serialize;
 use tokio::{
    net::TcpListener,
@@ -40,12 +43,11 @@ use tokio::{
    },
    task::{self, JoinError},
 };
-use tower_http::services::{ServeDir, ServeFile};
 use watchexec::error::CriticalError;
 
-// Initially, just rebuild everything. This can get smarter later!
 use crate::{
-   build::{self, build, config_for},
+   build::{self, Changed, Failure, Input, InputDirs, Memory, Mode, build, config_for},
+   cache::{self, Cache},
    canonicalized::Canonicalized,
    data::config::Config,
 };
@@ -71,13 +73,15 @@ pub fn serve(site_dir: &Utf8Path, port: Option<u16>) -> Result<(), Box<Error>> {
    let site_dir = Canonicalized::try_from(site_dir).map_err(Error::from)?;
    trace!("Building in {site_dir:?}");
 
-   // TODO: watch this separately?
+   // Changes to the config are picked up by `rebuild`; this is just the starting point.
    let config = config_for(&site_dir).map_err(Error::from)?;
    trace!("Computed config: {config:?}");
 
    // TODO: consider how to loop on rebuild and changes and *not serve* until there has
    // been a successful build.
-   let first_build = build(&site_dir, &config, &md, build::Mode::Serve);
+   let cache = Cache::for_site(site_dir.as_ref());
+   let output = Arc::new(Memory::default());
+   let first_build = build(&site_dir, &config, &md, &cache, Mode::Serve(&output));
    if let Err(e) = first_build {
       eprintln!("Initial build failed: {e:?}");
    }
@@ -90,12 +94,15 @@ pub fn serve(site_dir: &Utf8Path, port: Option<u16>) -> Result<(), Box<Error>> {
    let (change_tx, _) = broadcast::channel(8);
    let (rebuild_tx, _) = broadcast::channel(8);
 
-   let serve_handle = rt.spawn(serve_in(config.output.clone(), port, rebuild_tx.clone()));
-   let watch_handle = rt.spawn(watch_in(site_dir.clone(), change_tx.clone()));
+   let serve_handle = rt.spawn(serve_in(Arc::clone(&output), port, rebuild_tx.clone()));
+   let input_dirs = InputDirs::for_site(&site_dir);
+   let watch_handle = rt.spawn(watch_in(input_dirs, change_tx.clone()));
    let rebuild_handle = rt.spawn(rebuild(
       Arc::new(site_dir),
       Arc::new(config),
       Arc::new(md),
+      Arc::new(cache),
+      output,
       change_tx,
       rebuild_tx.clone(),
    ));
@@ -118,20 +125,21 @@ pub fn serve(site_dir: &Utf8Path, port: Option<u16>) -> Result<(), Box<Error>> {
 
 async fn rebuild(
    site_dir: Arc<Canonicalized>,
-   site_config: Arc<Config>,
+   mut site_config: Arc<Config>,
    md: Arc<Markdown>,
+   cache: Arc<Cache>,
+   output: Arc<Memory>,
    change: Sender<Change>,
    rebuild_tx: Sender<Rebuild>,
 ) -> Result<(), Error> {
    let mut change = change.subscribe();
    loop {
-      let rebuilt_for = match change.recv().await {
+      let changed = match change.recv().await {
          // If the channel closed, do not keep listening for further changes.
          Err(RecvError::Closed) => break,
 
-         // If there were *specific* changes sent, rebuild for those. (Presently this does
-         // nothing, but in the future I may update it to do more granular rebuilds.)
-         Ok(Change { paths }) => RebuiltFor::Paths(paths),
+         // If there were *specific* changes sent, rebuild only what they affect.
+         Ok(Change { paths }) => Some(paths),
 
          // If the channel explicit notifies that it lagged, that means some number of
          // changes were *missed*, which in turn means the file system saw changes that
@@ -139,70 +147,115 @@ async fn rebuild(
          // way to do so granularly.
          Err(RecvError::Lagged(skipped)) => {
             error!("FS change notification: lost {skipped} messages");
-            RebuiltFor::All
+            None
          }
       };
 
       // Skip the path iteration and allocation if it's not useful!
       if log_enabled!(log::Level::Trace) {
-         trace!("rebuilding because of change to file(s):\n\t{rebuilt_for}");
+         let paths = match &changed {
+            Some(paths) => paths
+               .iter()
+               .map(|Changed { path, input }| format!("{path} ({input:?})"))
+               .collect::<Vec<_>>()
+               .join("\n\t"),
+            None => "(unknown)".into(),
+         };
+         trace!("rebuilding because of change to file(s):\n\t{paths}");
+      }
+
+      // Everything depends on the config, so reload it before rebuilding anything. If
+      // it is invalid, keep serving the last good build until it is fixed.
+      let config_path = build::config_path(&site_dir);
+      let config_changed = changed
+         .as_ref()
+         .is_none_or(|paths| paths.iter().any(|changed| changed.input == Input::Config));
+
+      if config_changed {
+         match config_for(&site_dir) {
+            Ok(config) => {
+               info!("reloaded {config_path}");
+               site_config = Arc::new(config);
+            }
+            Err(err) => {
+               warn!("could not reload {config_path}: {err}");
+               notify(
+                  &rebuild_tx,
+                  Rebuild::Failure {
+                     failures: vec![Failure::new(Some(config_path), &err)],
+                  },
+               );
+               continue;
+            }
+         }
       }
 
       let site_dir = Arc::clone(&site_dir);
       let site_config = Arc::clone(&site_config);
       let md = Arc::clone(&md);
+      let cache = Arc::clone(&cache);
+      let task_output = Arc::clone(&output);
 
+      // Comparing the output before and after the build means clients only need to
+      // reload for output which actually changed.
+      let before = output.snapshot();
       let rebuild_task = task::spawn_blocking(move || {
-         build(&site_dir, &site_config, &md, build::Mode::Serve)
+         let output = task_output;
+         match changed {
+            Some(changed) => {
+               build::rebuild(&site_dir, &site_config, &md, &cache, &output, &changed)
+            }
+            None => build(&site_dir, &site_config, &md, &cache, Mode::Serve(&output)),
+         }
       });
 
       let rebuild = match rebuild_task.await {
-         Ok(build_result) => match build_result.map_err(Error::from) {
-            Ok(()) => {
-               info!("rebuild completed");
-               Rebuild::Success {
-                  for_changes: rebuilt_for,
-               }
+         Ok(Ok(())) => {
+            info!("rebuild completed");
+            Rebuild::Success {
+               for_changes: RebuiltFor(build::changes(&before, &output.snapshot())),
             }
-            Err(err) => {
-               warn!("rebuild failed: {err:#?}");
-               Rebuild::Failure {
-                  cause: err.to_string(),
-               }
+         }
+         Ok(Err(err)) => {
+            let failures = err.failures();
+            for failure in &failures {
+               warn!("rebuild failed: {:?}", failure.report());
             }
-         },
+            Rebuild::Failure { failures }
+         }
          Err(join_err) => {
             warn!("rebuild task panicked: {join_err:#?}");
             Rebuild::Failure {
-               cause: format!("rebuild task panicked: {join_err}"),
+               failures: vec![Failure::new(None, &join_err)],
             }
          }
       };
 
-      match rebuild_tx.send(rebuild) {
-         Ok(recv_count) => {
-            trace!("sent rebuild notification to {recv_count} open receivers");
-         }
-         Err(_rebuild) => {
-            trace!("no open receiver, so rebuild notification ignored");
-         }
-      }
+      notify(&rebuild_tx, rebuild);
    }
 
    Ok(())
 }
 
+fn notify(rebuild_tx: &Sender<Rebuild>, rebuild: Rebuild) {
+   match rebuild_tx.send(rebuild) {
+      Ok(recv_count) => {
+         trace!("sent rebuild notification to {recv_count} open receivers");
+      }
+      Err(_rebuild) => {
+         trace!("no open receiver, so rebuild notification ignored");
+      }
+   }
+}
+
 async fn serve_in(
-   path: Utf8PathBuf,
+   output: Arc<Memory>,
    port: Option<u16>,
    state: Sender<Rebuild>,
 ) -> Result<(), Error> {
-   // This could be extracted into its own function.
-   let serve_dir = ServeDir::new(&path).append_index_html_on_directories(true);
    let router = Router::new()
-      .route_service("/", ServeFile::new(path.join("index.html")))
-      .route_service("/*asset", serve_dir)
       .route("/live-reload", routing::get(websocket_upgrade))
+      .fallback(move |uri: Uri| serve_file(Arc::clone(&output), uri))
       .with_state(state);
 
    let port = port.unwrap_or(24747);
@@ -217,7 +270,7 @@ async fn serve_in(
    let local_ip = local_ip_address::local_ip().expect("can always get my own IP address");
 
    info!(
-      "→ Serving\n\tlocal: http://127.0.0.1:{port}\n\tnetwork: http://{local_ip}:{port}\n\tfrom {path}",
+      "→ Serving\n\tlocal: http://127.0.0.1:{port}\n\tnetwork: http://{local_ip}:{port}",
    );
 
    axum::serve(listener, router)
@@ -225,6 +278,60 @@ async fn serve_in(
       .map_err(|source| Error::ServeStart { source })
 }
 
+/// Serve a file from the most recent successful build, the same way a static file server
+/// would: directories serve their `index.html`, and get redirected to include a trailing
+/// slash so that relative links resolve correctly.
+async fn serve_file(output: Arc<Memory>, uri: Uri) -> Response {
+   let requested = percent_decode_str(uri.path()).decode_utf8_lossy();
+   let relative = Utf8Path::new(requested.trim_start_matches('/'));
+   let files = output.snapshot();
+
+   let is_dir = relative.as_str().is_empty() || requested.ends_with('/');
+   let path = if is_dir {
+      relative.join("index.html")
+   } else {
+      relative.to_owned()
+   };
+
+   if let Some(content) = files.get(&path) {
+      let mime = mime_guess::from_path(&path).first_or_octet_stream();
+      let content = if mime == mime_guess::mime::TEXT_HTML {
+         with_live_reload(content)
+      } else {
+         content.clone()
+      };
+
+      return ([(header::CONTENT_TYPE, mime.as_ref())], content).into_response();
+   }
+
+   if !is_dir && files.contains_key(&relative.join("index.html")) {
+      return Redirect::temporary(&format!("{}/", uri.path())).into_response();
+   }
+
+   debug!("no output for {uri}");
+   StatusCode::NOT_FOUND.into_response()
+}
+
+/// The client for `/live-reload`, which is only ever injected by this server, so that
+/// it never ends up in published output.
+const LIVE_RELOAD_CLIENT: &str = include_str!("server/live-reload.js");
+
+/// Add the live-reload client to an HTML page, at the end of its `<body>` if it has one
+/// or at the end of the document otherwise.
+fn with_live_reload(html: &Bytes) -> Bytes {
+   let script = format!("<script>{LIVE_RELOAD_CLIENT}</script>");
+   let insert_at = html
+      .windows(b"</body>".len())
+      .rposition(|window| window.eq_ignore_ascii_case(b"</body>"))
+      .unwrap_or(html.len());
+
+   let mut injected = Vec::with_capacity(html.len() + script.len());
+   injected.extend_from_slice(&html[..insert_at]);
+   injected.extend_from_slice(script.as_bytes());
+   injected.extend_from_slice(&html[insert_at..]);
+   Bytes::from(injected)
+}
+
 async fn websocket_upgrade(
    extractor: WebSocketUpgrade,
    State(state): State<Sender<Rebuild>>,
@@ -249,14 +356,7 @@ async fn websocket(socket: WebSocket, rebuild_tx: Sender<Rebuild>) {
                Rebuild::Success {
                   for_changes: rebuilt_for,
                } => {
-                  let rebuild_desc = match &rebuilt_for {
-                     RebuiltFor::Paths(path_bufs) => path_bufs
-                        .iter()
-                        .map(|p| p.to_string_lossy())
-                        .collect::<Vec<_>>()
-                        .join("\n\t"),
-                     RebuiltFor::All => "(all)".into(),
-                  };
+                  let rebuild_desc = rebuilt_for.to_string();
 
                   debug!(
                      "sending WebSocket reload message with paths:\n\t{rebuild_desc}"
@@ -271,12 +371,18 @@ async fn websocket(socket: WebSocket, rebuild_tx: Sender<Rebuild>) {
                      Err(reason) => error!("Could not send WebSocket message:\n{reason}"),
                   }
                }
-               Rebuild::Failure { cause } => {
-                  todo!(
-                     "send message about errors to client.\
-                        Make it easy to notice and debug on either side!\
-                        cause: {cause}"
-                  )
+               Rebuild::Failure { failures } => {
+                  debug!(
+                     "sending WebSocket error message for {} failures",
+                     failures.len()
+                  );
+
+                  let payload = serde_json::to_string(&ChangePayload::Error { failures })
+                     .unwrap_or_else(|e| panic!("Could not serialize payload: {e}"));
+
+                  if let Err(reason) = ws_tx.send(Message::Text(payload)).await {
+                     error!("Could not send WebSocket message:\n{reason}");
+                  }
                }
             },
             Err(_) => {
@@ -350,6 +456,7 @@ fn handle(
 #[derive(Debug, Serialize)]
 enum ChangePayload {
    Reload { rebuilt_for: RebuiltFor },
+   Error { failures: Vec<Failure> },
 }
 
 #[derive(Debug)]
@@ -373,37 +480,27 @@ impl std::fmt::Display for WebSocketState {
 
 #[derive(Debug, Clone)]
 struct Change {
-   pub paths: Vec<PathBuf>,
+   pub paths: Vec<Changed>,
 }
 
 #[derive(Debug, Clone)]
 pub enum Rebuild {
    Success { for_changes: RebuiltFor },
-   Failure { cause: String },
+   Failure { failures: Vec<Failure> },
 }
 
+/// The output paths, relative to the root of the site, which changed in a rebuild.
 #[derive(Debug, Clone, Serialize)]
-pub enum RebuiltFor {
-   Paths(Vec<PathBuf>),
-   All,
-}
+pub struct RebuiltFor(Vec<Utf8PathBuf>);
 
 impl fmt::Display for RebuiltFor {
    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-      let s = match self {
-         RebuiltFor::Paths(path_bufs) => path_bufs
-            .iter()
-            .map(|p| p.to_string_lossy())
-            .collect::<Vec<_>>()
-            .join("\n\t"),
-         RebuiltFor::All => "(all)".into(),
-      };
-
-      write!(f, "{s}")
+      let paths = self.0.iter().map(|p| p.as_str()).collect::<Vec<_>>();
+      write!(f, "{}", paths.join("\n\t"))
    }
 }
 
-async fn watch_in(input: Canonicalized, change_tx: Sender<Change>) -> Result<(), Error> {
+async fn watch_in(inputs: InputDirs, change_tx: Sender<Change>) -> Result<(), Error> {
    let (tx, mut rx) = mpsc::channel(8);
 
    // Doing this here means we will not drop the watcher until this function
@@ -419,26 +516,52 @@ async fn watch_in(input: Canonicalized, change_tx: Sender<Change>) -> Result<(),
       },
    )?;
 
-   let paths = input
-      .as_ref()
-      .read_dir()
-      .map_err(|source| Error::Io { source })?
-      .filter_map(|p| p.ok().map(|p| p.path()))
-      .filter(|p| !is_public(input.as_ref(), p))
-      .collect::<Vec<PathBuf>>();
-
-   for path in paths {
-      debug!("Adding {} to watched paths", path.display());
-      debouncer.watch(&path, RecursiveMode::Recursive)?;
+   // Watch each input directory itself only non-recursively, so that replacing a file
+   // in it (as many editors do when saving, e.g. the config file) does not lose the
+   // watch, while still not watching the output directory.
+   for input in inputs.existing() {
+      debug!("Adding {input} to watched paths");
+      debouncer.watch(input, RecursiveMode::NonRecursive)?;
+
+      let dirs = input
+         .read_dir()
+         .map_err(|source| Error::Io { source })?
+         .filter_map(|p| p.ok().map(|p| p.path()))
+         .filter(|p| p.is_dir() && !is_ignored(&inputs.site, p))
+         .collect::<Vec<PathBuf>>();
+
+      for dir in dirs {
+         debug!("Adding {} to watched paths", dir.display());
+         debouncer.watch(&dir, RecursiveMode::Recursive)?;
+      }
    }
 
    while let Some(result) = rx.recv().await {
       // Might want to handle debounce errors without closing this?
-      let paths = result
+      let mut paths = Vec::<Changed>::new();
+      for path in result
          .map_err(Error::DebounceErrors)?
          .into_iter()
+         // Reading files, including during a build, is not a change to rebuild for.
+         .filter(|DebouncedEvent { event, .. }| {
+            !matches!(event.kind, EventKind::Access(_))
+         })
          .flat_map(|DebouncedEvent { event, .. }| event.paths)
-         .collect::<Vec<_>>();
+         .filter(|path| !is_ignored(&inputs.site, path))
+      {
+         match Utf8PathBuf::try_from(path) {
+            Ok(path) if paths.iter().any(|changed| changed.path == path) => {}
+            Ok(path) => paths.push(inputs.changed(path)),
+            Err(e) => warn!(
+               "ignoring change to non-UTF-8 path {}",
+               e.as_path().display()
+            ),
+         }
+      }
+
+      if paths.is_empty() {
+         continue;
+      }
 
       let change = Change { paths };
       if let Err(e) = change_tx.send(change) {
@@ -449,15 +572,16 @@ async fn watch_in(input: Canonicalized, change_tx: Sender<Change>) -> Result<(),
    Ok(())
 }
 
-fn is_public<P1: AsRef<Path>, P2: AsRef<Path>>(root: P1, desc: P2) -> bool {
+/// Whether a path is one the build writes rather than reads: the output directory and the
+/// build cache.
+fn is_ignored<P1: AsRef<Path>, P2: AsRef<Path>>(root: P1, desc: P2) -> bool {
    let root = root.as_ref();
    let desc = desc.as_ref();
-   let out = desc
-      .strip_prefix(root)
-      .expect("Never call this on paths which are not children of the root")
-      .starts_with("public");
+   let out = desc.strip_prefix(root).is_ok_and(|relative| {
+      relative.starts_with("public") || relative.starts_with(cache::DIR_NAME)
+   });
    trace!(
-      "checking whether {} is in {}/public: {}",
+      "checking whether {} is ignored in {}: {}",
       desc.display(),
       root.display(),
       if out { "yes " } else { "no" }
@@ -568,3 +692,24 @@ where
 {
    future::select_all(futures).await.0
 }
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn live_reload_goes_at_the_end_of_the_body() {
+      let html = Bytes::from("<html><body><p>Hi</p></BODY></html>");
+      let injected = String::from_utf8(with_live_reload(&html).to_vec()).unwrap();
+      assert!(injected.starts_with("<html><body><p>Hi</p><script>"));
+      assert!(injected.ends_with("</script></BODY></html>"));
+   }
+
+   #[test]
+   fn live_reload_without_a_body() {
+      let html = Bytes::from("<p>Hi</p>");
+      let injected = String::from_utf8(with_live_reload(&html).to_vec()).unwrap();
+      assert!(injected.starts_with("<p>Hi</p><script>"));
+      assert!(injected.ends_with("</script>"));
+   }
+}
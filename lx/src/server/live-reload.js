@@ -0,0 +1,203 @@
+// The live-reload client `lx develop` injects into every HTML page it serves.
+//
+// It listens for rebuilds on the `/live-reload` WebSocket and:
+//
+// - swaps out any changed stylesheets in place, without reloading the page
+// - reloads the page only when its own output changed
+// - shows build failures in an overlay, which clears on the next successful build
+// - reconnects with backoff when the connection drops, e.g. when `lx` restarts
+(() => {
+   const Level = {
+      Off: 0,
+      Error: 1,
+      Warn: 2,
+      Info: 3,
+      Debug: 4,
+      Trace: 5,
+   };
+
+   const OVERLAY_ID = 'lx-build-errors';
+   const MIN_BACKOFF_MS = 250;
+   const MAX_BACKOFF_MS = 10_000;
+
+   let backoff = MIN_BACKOFF_MS;
+   let wasConnected = false;
+
+   connect();
+
+   function connect() {
+      const protocol = location.protocol === 'https:' ? 'wss:' : 'ws:';
+      const ws = new WebSocket(`${protocol}//${location.host}/live-reload`);
+      debug('created WebSocket instance');
+
+      ws.addEventListener('open', () => {
+         debug('WebSocket connection opened');
+         backoff = MIN_BACKOFF_MS;
+
+         // If this is a *re*-connection, the server restarted and rebuilt everything,
+         // so there is no way to know whether this page changed.
+         if (wasConnected) {
+            location.reload();
+         }
+         wasConnected = true;
+      });
+
+      ws.addEventListener('error', (event) => {
+         debug('error:', event);
+      });
+
+      ws.addEventListener('close', (closeEvent) => {
+         const message = `closed because ${closeEvent.reason || '(no reason given)'}`;
+         if (closeEvent.wasClean) {
+            debug(message);
+         } else {
+            info(message, closeEvent.code);
+         }
+
+         info(`reconnecting in ${backoff}ms`);
+         setTimeout(connect, backoff);
+         backoff = Math.min(backoff * 2, MAX_BACKOFF_MS);
+      });
+
+      ws.addEventListener('message', (message) => {
+         let payload;
+         try {
+            payload = JSON.parse(message.data);
+            debug('Parsed JSON payload of WebSocket message', payload);
+         } catch (e) {
+            error('Error deserializing WebSocket message', e);
+            return;
+         }
+
+         if (payload.Reload) {
+            clearErrors();
+            handleReload(payload.Reload.rebuilt_for);
+         }
+
+         if (payload.Error) {
+            showErrors(payload.Error.failures);
+         }
+      });
+
+      debug('added all WebSocket listeners');
+   }
+
+   /** @param {string[]} rebuiltFor */
+   function handleReload(rebuiltFor) {
+      debug('rebuilt for', rebuiltFor);
+      const changed = new Set(rebuiltFor);
+
+      if (changed.has(currentOutputPath())) {
+         location.reload();
+         return;
+      }
+
+      for (const link of document.querySelectorAll('link[rel="stylesheet"]')) {
+         const url = new URL(link.href, location.href);
+         if (url.origin !== location.origin) continue;
+
+         const path = decodeURIComponent(url.pathname).replace(/^\//, '');
+         if (changed.has(path)) {
+            debug('swapping stylesheet', path);
+            url.searchParams.set('lx-reload', String(Date.now()));
+            link.href = url.toString();
+         }
+      }
+   }
+
+   /** The output file for the current page, in the form the server reports it. */
+   function currentOutputPath() {
+      const path = decodeURIComponent(location.pathname).replace(/^\//, '');
+      return path === '' || path.endsWith('/') ? `${path}index.html` : path;
+   }
+
+   /**
+    * @param {Array<{
+    *   path: string | null,
+    *   chain: string[],
+    *   line: { file: string, number: number, column: number, text: string } | null,
+    * }>} failures
+    */
+   function showErrors(failures) {
+      error('build failed', failures);
+      clearErrors();
+
+      const overlay = document.createElement('div');
+      overlay.id = OVERLAY_ID;
+      overlay.style.cssText = `
+         position: fixed;
+         inset: 0;
+         z-index: 2147483647;
+         overflow: auto;
+         padding: 2rem;
+         background: rgb(0 0 0 / 0.85);
+         color: white;
+         font: 14px/1.5 ui-monospace, monospace;
+      `;
+
+      const heading = document.createElement('h1');
+      heading.textContent = 'Build failed';
+      heading.style.cssText = 'font-size: 1.5rem; color: #ff8080;';
+      overlay.append(heading);
+
+      for (const failure of failures) {
+         const section = document.createElement('section');
+         section.style.cssText = 'margin-block: 1.5rem;';
+
+         // The line may be in a template rather than in the file being built, so
+         // name both when they differ.
+         const { path, line } = failure;
+         const location = line ? `${line.file}:${line.number}:${line.column}` : path;
+         if (location) {
+            const heading = document.createElement('h2');
+            heading.textContent =
+               path && line && line.file !== path
+                  ? `${location} (building ${path})`
+                  : location;
+            heading.style.cssText = 'font-size: 1rem;';
+            section.append(heading);
+         }
+
+         const chain = document.createElement('pre');
+         chain.textContent = failure.chain.join('\n  caused by: ');
+         chain.style.cssText = 'white-space: pre-wrap;';
+         section.append(chain);
+
+         if (failure.line) {
+            const line = document.createElement('pre');
+            const { number, column, text } = failure.line;
+            const gutter = ' '.repeat(String(number).length);
+            line.textContent =
+               `${number} | ${text}\n` + `${gutter} | ${' '.repeat(column - 1)}^`;
+            line.style.cssText = 'padding: 0.5rem; background: rgb(255 255 255 / 0.1);';
+            section.append(line);
+         }
+
+         overlay.append(section);
+      }
+
+      document.body.append(overlay);
+   }
+
+   function clearErrors() {
+      document.getElementById(OVERLAY_ID)?.remove();
+   }
+
+   function error(...messages) {
+      if (logLevel() >= Level.Error) console.error('[lx] [Error]', ...messages);
+   }
+
+   function info(...messages) {
+      if (logLevel() >= Level.Info) console.info('[lx] [Info]', ...messages);
+   }
+
+   function debug(...messages) {
+      if (logLevel() >= Level.Debug) console.info('[lx] [Debug]', ...messages);
+   }
+
+   /** Set `lx:logLevel` in local storage to one of the `Level` names to change it. */
+   function logLevel() {
+      const name = globalThis.localStorage.getItem('lx:logLevel') ?? 'Info';
+      return Level[name] ?? Level.Info;
+   }
+})();
@@ -0,0 +1,396 @@
+//! Configurable taxonomies, e.g. `categories`, declared in `config.lx.yaml` and used in
+//! the metadata for each item.
+
+use std::collections::{BTreeMap, HashSet, btree_map::Entry};
+
+use camino::Utf8PathBuf;
+use serde::{Deserialize, Serialize};
+use serde_yaml::Value;
+use slug::slugify;
+use thiserror::Error;
+
+use crate::page::Item;
+
+/// A taxonomy as declared in the site config.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Taxonomy {
+   /// The name of the taxonomy, which is also the key used for it in item metadata.
+   pub name: String,
+
+   /// Whether terms in this taxonomy can be nested. See `docs/src/config/hierarchical.md`.
+   #[serde(default)]
+   pub hierarchical: bool,
+
+   /// The layout to use for the page for each term. Defaults to `taxonomy.jinja`.
+   pub layout: Option<String>,
+}
+
+impl Taxonomy {
+   /// Every item's `tags` form a built-in flat taxonomy, with pages rendered using the
+   /// `tag.jinja` layout. (A configured taxonomy named `tags` would collide with it.)
+   pub fn tags() -> Taxonomy {
+      Taxonomy {
+         name: String::from("tags"),
+         hierarchical: false,
+         layout: Some(String::from("tag.jinja")),
+      }
+   }
+
+   pub fn layout(&self) -> &str {
+      self.layout.as_deref().unwrap_or("taxonomy.jinja")
+   }
+
+   /// Where the taxonomy's pages live, relative to the root of the site.
+   pub fn path(&self) -> Utf8PathBuf {
+      Utf8PathBuf::from(slugify(&self.name))
+   }
+
+   /// Parse and validate the terms for this taxonomy from an item's metadata. Terms may
+   /// be a single string or a list of strings; in a hierarchical taxonomy, each entry in
+   /// the list may also be a map from a term to its own list of child terms:
+   ///
+   /// ```yaml
+   /// categories:
+   ///   - tech:
+   ///     - programming languages
+   /// ```
+   ///
+   /// The item then belongs to both `tech` and `tech/programming languages`.
+   pub fn terms_from(&self, value: &Value) -> Result<Vec<Term>, Error> {
+      let mut terms = Vec::new();
+      self.collect_terms(value, &[], &mut terms)?;
+
+      let mut seen = HashSet::new();
+      terms.retain(|term| seen.insert(term.clone()));
+      Ok(terms)
+   }
+
+   fn collect_terms(
+      &self,
+      value: &Value,
+      parent: &[String],
+      terms: &mut Vec<Term>,
+   ) -> Result<(), Error> {
+      match value {
+         Value::Null => Ok(()),
+
+         Value::String(name) => {
+            terms.push(Term::child_of(parent, name));
+            Ok(())
+         }
+
+         Value::Sequence(values) => values
+            .iter()
+            .try_for_each(|value| self.collect_terms(value, parent, terms)),
+
+         Value::Mapping(mapping) => {
+            for (key, children) in mapping {
+               let Value::String(name) = key else {
+                  return Err(self.bad_term(key));
+               };
+
+               if !self.hierarchical {
+                  return Err(Error::Nested {
+                     taxonomy: self.name.clone(),
+                     term: name.clone(),
+                  });
+               }
+
+               let term = Term::child_of(parent, name);
+               terms.push(term.clone());
+               self.collect_terms(children, &term.0, terms)?;
+            }
+            Ok(())
+         }
+
+         other => Err(self.bad_term(other)),
+      }
+   }
+
+   fn bad_term(&self, value: &Value) -> Error {
+      Error::BadTerm {
+         taxonomy: self.name.clone(),
+         found: format!("{value:?}"),
+      }
+   }
+}
+
+/// A single term in a taxonomy, represented as the full path to it from the root of the
+/// taxonomy, so `tech/programming languages` is `["tech", "programming languages"]`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct Term(Vec<String>);
+
+impl Term {
+   fn child_of(parent: &[String], name: &str) -> Term {
+      let mut path = parent.to_vec();
+      path.push(name.to_owned());
+      Term(path)
+   }
+
+   /// The term's own name, without its parents.
+   pub fn name(&self) -> &str {
+      self.0.last().map(String::as_str).unwrap_or_default()
+   }
+
+   /// Where the term's page lives, relative to its taxonomy's path.
+   pub fn path(&self) -> Utf8PathBuf {
+      self.0.iter().map(slugify).collect()
+   }
+
+   /// Whether the terms are the same apart from case, e.g. `Rust` and `rust`, and so
+   /// belong on the same page.
+   fn matches(&self, other: &Term) -> bool {
+      self.0.len() == other.0.len()
+         && self
+            .0
+            .iter()
+            .zip(&other.0)
+            .all(|(a, b)| a.to_lowercase() == b.to_lowercase())
+   }
+}
+
+/// Groups items by where each term's page goes, since different terms can have the same
+/// slug. Terms which differ only in case share a page, under whichever name sorts first;
+/// any other terms with the same slug, like `C` and `C++`, would overwrite each other.
+struct ByPath<'t, 'i, T: ?Sized> {
+   taxonomy: &'t str,
+   terms: BTreeMap<Utf8PathBuf, (Term, Vec<&'i T>)>,
+}
+
+impl<'t, 'i, T: ?Sized> ByPath<'t, 'i, T> {
+   fn new(taxonomy: &'t str) -> Self {
+      ByPath {
+         taxonomy,
+         terms: BTreeMap::new(),
+      }
+   }
+
+   fn insert(&mut self, term: &Term, item: &'i T) -> Result<(), Error> {
+      match self.terms.entry(term.path()) {
+         Entry::Vacant(entry) => {
+            entry.insert((term.clone(), vec![item]));
+         }
+
+         Entry::Occupied(mut entry) => {
+            let (existing, items) = entry.get_mut();
+            if !existing.matches(term) {
+               return Err(Error::Collision {
+                  taxonomy: self.taxonomy.to_owned(),
+                  path: term.path(),
+                  first: existing.0.join("/"),
+                  second: term.0.join("/"),
+               });
+            }
+
+            if term < existing {
+               *existing = term.clone();
+            }
+
+            // An item may use more than one spelling of the same term.
+            if !items.last().is_some_and(|last| std::ptr::eq(*last, item)) {
+               items.push(item);
+            }
+         }
+      }
+
+      Ok(())
+   }
+
+   fn into_terms(self) -> BTreeMap<Term, Vec<&'i T>> {
+      self.terms.into_values().collect()
+   }
+}
+
+/// Every term in a taxonomy, along with the items which use it.
+pub struct Terms<'i> {
+   pub items: BTreeMap<Term, Vec<&'i Item<'i>>>,
+}
+
+impl<'i> Terms<'i> {
+   pub fn collect(
+      taxonomy: &Taxonomy,
+      items: &'i [Item<'i>],
+   ) -> Result<Terms<'i>, Error> {
+      let mut by_path = ByPath::new(&taxonomy.name);
+      for item in items {
+         let terms = item.data().taxonomies.get(&taxonomy.name);
+         for term in terms.into_iter().flatten() {
+            by_path.insert(term, item)?;
+         }
+      }
+
+      Ok(Terms {
+         items: by_path.into_terms(),
+      })
+   }
+
+   /// Collect the built-in tags taxonomy. Unlike configured taxonomies, only posts are
+   /// included, since tag pages are for browsing by topic.
+   pub fn tags(items: &'i [Item<'i>]) -> Result<Terms<'i>, Error> {
+      let mut by_path = ByPath::new("tags");
+      for item in items.iter().filter(|item| matches!(item, Item::Post(_))) {
+         for tag in &item.data().tags {
+            by_path.insert(&Term(vec![tag.clone()]), item)?;
+         }
+      }
+
+      Ok(Terms {
+         items: by_path.into_terms(),
+      })
+   }
+
+   /// A summary of each term, suitable for an index of the whole taxonomy.
+   pub fn summaries(&self) -> Vec<TermSummary<'_>> {
+      self
+         .items
+         .iter()
+         .map(|(term, items)| TermSummary {
+            name: term.name(),
+            path: term.path(),
+            count: items.len(),
+         })
+         .collect()
+   }
+}
+
+/// An entry in the index of a taxonomy: the term, where its page lives relative to the
+/// taxonomy's own path, and how many items use it.
+#[derive(Debug, Serialize)]
+pub struct TermSummary<'t> {
+   pub name: &'t str,
+   pub path: Utf8PathBuf,
+   pub count: usize,
+}
+
+#[derive(Error, Debug)]
+pub enum Error {
+   #[error("taxonomy '{taxonomy}' is not hierarchical, but '{term}' has nested terms")]
+   Nested { taxonomy: String, term: String },
+
+   #[error("invalid term for taxonomy '{taxonomy}': {found}")]
+   BadTerm { taxonomy: String, found: String },
+
+   #[error(
+      "terms '{first}' and '{second}' in taxonomy '{taxonomy}' would both go at '{path}'"
+   )]
+   Collision {
+      taxonomy: String,
+      path: Utf8PathBuf,
+      first: String,
+      second: String,
+   },
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   fn taxonomy(hierarchical: bool) -> Taxonomy {
+      Taxonomy {
+         name: String::from("categories"),
+         hierarchical,
+         layout: None,
+      }
+   }
+
+   fn term(path: &[&str]) -> Term {
+      Term(path.iter().map(|s| s.to_string()).collect())
+   }
+
+   #[test]
+   fn terms_differing_only_in_case_share_a_page() {
+      let mut by_path = ByPath::new("tags");
+      by_path.insert(&term(&["rust"]), "a").unwrap();
+      by_path.insert(&term(&["Rust"]), "b").unwrap();
+      by_path.insert(&term(&["rust"]), "b").unwrap();
+
+      let terms = by_path.into_terms();
+      assert_eq!(terms.len(), 1);
+      assert_eq!(terms[&term(&["Rust"])], vec!["a", "b"]);
+   }
+
+   #[test]
+   fn terms_with_the_same_slug_collide() {
+      let mut by_path = ByPath::new("tags");
+      by_path.insert(&term(&["C"]), "a").unwrap();
+
+      assert!(matches!(
+         by_path.insert(&term(&["C++"]), "b"),
+         Err(Error::Collision { path, .. }) if path == "c"
+      ));
+   }
+
+   #[test]
+   fn flat_terms() {
+      let value = serde_yaml::from_str("[tech, art]").unwrap();
+      assert_eq!(
+         taxonomy(false).terms_from(&value).unwrap(),
+         vec![term(&["tech"]), term(&["art"])]
+      );
+   }
+
+   #[test]
+   fn single_term() {
+      let value = serde_yaml::from_str("tech").unwrap();
+      assert_eq!(
+         taxonomy(false).terms_from(&value).unwrap(),
+         vec![term(&["tech"])]
+      );
+   }
+
+   #[test]
+   fn nested_terms() {
+      let value = serde_yaml::from_str(
+         "- tech:\n  - programming languages\n  - agile software development\n",
+      )
+      .unwrap();
+
+      assert_eq!(
+         taxonomy(true).terms_from(&value).unwrap(),
+         vec![
+            term(&["tech"]),
+            term(&["tech", "programming languages"]),
+            term(&["tech", "agile software development"]),
+         ]
+      );
+   }
+
+   #[test]
+   fn nested_terms_in_flat_taxonomy() {
+      let value = serde_yaml::from_str("- tech:\n  - programming languages\n").unwrap();
+      assert!(matches!(
+         taxonomy(false).terms_from(&value),
+         Err(Error::Nested { .. })
+      ));
+   }
+
+   #[test]
+   fn duplicate_terms_are_merged() {
+      let value = serde_yaml::from_str("- tech\n- tech: [rust]\n").unwrap();
+      assert_eq!(
+         taxonomy(true).terms_from(&value).unwrap(),
+         vec![term(&["tech"]), term(&["tech", "rust"])]
+      );
+   }
+
+   #[test]
+   fn summaries_count_items() {
+      let terms = Terms {
+         items: BTreeMap::from([(term(&["rust"]), Vec::new())]),
+      };
+
+      let summaries = terms.summaries();
+      assert_eq!(summaries.len(), 1);
+      assert_eq!(summaries[0].name, "rust");
+      assert_eq!(summaries[0].path, Utf8PathBuf::from("rust"));
+      assert_eq!(summaries[0].count, 0);
+   }
+
+   #[test]
+   fn term_path_is_slugified() {
+      assert_eq!(
+         term(&["Tech", "Programming Languages"]).path(),
+         Utf8PathBuf::from("tech/programming-languages")
+      );
+   }
+}
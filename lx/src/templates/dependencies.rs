@@ -0,0 +1,130 @@
+//! Which templates depend on which others, so that a change to one template only
+//! requires re-rendering the output which uses it.
+
+use std::collections::{HashMap, HashSet};
+
+use lazy_static::lazy_static;
+use minijinja::Environment;
+use regex::Regex;
+
+lazy_static! {
+   /// Matches `{% extends "x" %}`, `{% include "x" %}`, `{% import "x" as y %}`, and
+   /// `{% from "x" import y %}`. Template names computed at render time cannot be
+   /// tracked this way, but the site templates never do that.
+   static ref REFERENCE_RE: Regex = Regex::new(
+      r#"\{%-?\s*(?:extends|include|import|from)\s+["']([^"']+)["']"#
+   )
+   .unwrap();
+}
+
+#[derive(Debug, Default)]
+pub struct Dependencies {
+   /// For each template, the templates which refer to it directly.
+   dependents: HashMap<String, HashSet<String>>,
+
+   /// The templates which use the `site` global directly, and so need to be rendered
+   /// again whenever any item changes.
+   uses_site: HashSet<String>,
+}
+
+impl Dependencies {
+   pub fn new(env: &Environment) -> Dependencies {
+      let mut dependencies = Dependencies::default();
+      for (name, template) in env.templates() {
+         for referenced in references(template.source()) {
+            dependencies
+               .dependents
+               .entry(referenced)
+               .or_default()
+               .insert(name.to_owned());
+         }
+
+         if template.undeclared_variables(false).contains("site") {
+            dependencies.uses_site.insert(name.to_owned());
+         }
+      }
+      dependencies
+   }
+
+   /// The given templates along with every template which refers to them, directly or
+   /// indirectly: i.e., every template whose output they can change.
+   pub fn affected_by<S: AsRef<str>>(
+      &self,
+      changed: impl IntoIterator<Item = S>,
+   ) -> HashSet<String> {
+      let mut affected = HashSet::new();
+      let mut pending = changed
+         .into_iter()
+         .map(|name| name.as_ref().to_owned())
+         .collect::<Vec<_>>();
+
+      while let Some(name) = pending.pop() {
+         if let Some(dependents) = self.dependents.get(&name) {
+            pending.extend(
+               dependents
+                  .iter()
+                  .filter(|d| !affected.contains(*d))
+                  .cloned(),
+            );
+         }
+         affected.insert(name);
+      }
+
+      affected
+   }
+
+   /// Every template whose output can include data from the `site` global.
+   pub fn using_site(&self) -> HashSet<String> {
+      self.affected_by(&self.uses_site)
+   }
+}
+
+/// The names of the templates a template's source refers to.
+pub fn references(source: &str) -> Vec<String> {
+   REFERENCE_RE
+      .captures_iter(source)
+      .map(|captures| captures[1].to_owned())
+      .collect()
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+
+   #[test]
+   fn finds_references() {
+      let source = r#"{% extends 'base.jinja' %}
+         {%- include "includes/pagination.jinja" %}
+         {% from "macros/note.jinja" import note %}"#;
+
+      assert_eq!(
+         references(source),
+         vec![
+            "base.jinja",
+            "includes/pagination.jinja",
+            "macros/note.jinja"
+         ]
+      );
+   }
+
+   #[test]
+   fn affected_templates_are_transitive() {
+      let mut env = Environment::new();
+      env.add_template("base.jinja", "{{ site.items | length }}")
+         .unwrap();
+      env.add_template("page.jinja", "{% extends 'base.jinja' %}")
+         .unwrap();
+      env.add_template("post.jinja", "{% extends 'page.jinja' %}")
+         .unwrap();
+      env.add_template("other.jinja", "hello").unwrap();
+
+      let dependencies = Dependencies::new(&env);
+      let expected = ["base.jinja", "page.jinja", "post.jinja"]
+         .map(String::from)
+         .into_iter()
+         .collect::<HashSet<_>>();
+
+      assert_eq!(dependencies.affected_by(["base.jinja"]), expected);
+      assert_eq!(dependencies.using_site(), expected);
+   }
+}
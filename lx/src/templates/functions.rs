@@ -4,12 +4,11 @@ use minijinja::{
    State, Value, context,
    value::{Object, Rest, ViaDeserialize},
 };
-use simplelog::debug;
 
 use crate::{
    data::{config::Config, image::Image, item::Metadata},
-   page::{self, RootedPath},
-   templates::component::{self, Component},
+   page::RootedPath,
+   templates::component::Component,
 };
 
 pub(crate) fn add_all(env: &mut minijinja::Environment<'_>) {
@@ -59,13 +58,17 @@ fn description(
 ) -> String {
    page_data
       .summary
-      .map(|summary| summary.plain())
+      .map(|summary| summary.plain().to_string())
       .or(
          page_data
             .book
             .and_then(|book| book.review.map(|review| review.to_string())),
       )
-      .or(page_data.subtitle.map(|subtitle| subtitle.plain()))
+      .or(
+         page_data
+            .subtitle
+            .map(|subtitle| subtitle.plain().to_string()),
+      )
       .unwrap_or_else(|| truncate(&nanohtml2text::html2text(content)))
 }
 
@@ -112,12 +115,6 @@ enum Label {
       duration: String,
       instrumentation: String,
    },
-   Custom {
-      label1: String,
-      data1: String,
-      label2: String,
-      data2: String,
-   },
 }
 
 impl Label {
@@ -141,7 +138,6 @@ impl Label {
       match self {
          Label::Post { .. } => "Author",
          Label::Work { .. } => "Instrumentation",
-         Label::Custom { label1, .. } => label1.as_str(),
       }
    }
 
@@ -151,7 +147,6 @@ impl Label {
          Label::Work {
             instrumentation, ..
          } => instrumentation.to_owned(),
-         Label::Custom { data1, .. } => data1.clone(),
       }
    }
 
@@ -159,7 +154,6 @@ impl Label {
       match self {
          Label::Post { .. } => "Length",
          Label::Work { .. } => "Duration",
-         Label::Custom { label2, .. } => label2.as_str(),
       }
    }
 
@@ -167,7 +161,6 @@ impl Label {
       match self {
          Label::Post { length, .. } => length.to_string(),
          Label::Work { duration, .. } => duration.clone(),
-         Label::Custom { data2, .. } => data2.clone(),
       }
    }
 }
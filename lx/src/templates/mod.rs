@@ -1,6 +1,8 @@
 pub mod component;
+pub mod dependencies;
 mod filters;
 mod functions;
+pub mod site;
 
 use std::io::Write;
 
@@ -11,8 +13,12 @@ use serde::Serialize;
 use thiserror::Error;
 
 use crate::{
+   archive::Archive,
    data::{config::Config, item::Metadata},
    page::{Item, RootedPath, Source},
+   pagination::Pagination,
+   publication::Unpublished,
+   series,
 };
 
 #[derive(Error, Debug)]
@@ -76,6 +82,9 @@ where
 pub fn render(
    env: &Environment,
    item: &Item,
+   series: Option<series::Context>,
+   pagination: Option<&Pagination<&Item>>,
+   unpublished: Option<Unpublished>,
    site: &Config,
    into: impl Write,
 ) -> Result<(), Error> {
@@ -88,11 +97,17 @@ pub fn render(
       config: &'a Config,
       path: &'a RootedPath,
       source: &'a Source,
+      series: Option<series::Context<'a>>,
+      #[serde(skip_serializing_if = "Option::is_none")]
+      pagination: Option<&'a Pagination<'a, &'a Item<'a>>>,
+      /// Why the item is not published yet, if it is not, so that templates can mark
+      /// drafts and scheduled items when they are included, e.g. in `lx develop`.
+      unpublished: Option<Unpublished>,
    }
 
    debug!(
       "Rendering page '{}' ({:?}) with layout '{}'",
-      item.title(),
+      item.source().path,
       item.path(),
       item.layout()
    );
@@ -111,6 +126,9 @@ pub fn render(
          config: site,
          path: item.path(),
          source: item.source(),
+         series,
+         pagination,
+         unpublished,
       },
       into,
    )
@@ -120,3 +138,112 @@ pub fn render(
       path: item.source().path.clone(),
    })
 }
+
+/// Render a templated, non-HTML output such as `feed.lx.xml`. The source file *is* the
+/// template, and it has access to every item on the site as well as the archive.
+pub fn render_templated(
+   env: &Environment,
+   source: &Source,
+   items: &[Item],
+   archive: &Archive,
+   site: &Config,
+   into: impl Write,
+) -> Result<(), Error> {
+   #[derive(Serialize)]
+   struct Context<'a> {
+      config: &'a Config,
+      items: &'a [Item<'a>],
+      archive: &'a Archive<'a>,
+      source: &'a Source,
+   }
+
+   debug!("Rendering templated file '{}'", source.path);
+
+   // Naming the template after the source file gets it correct auto-escaping for its
+   // extension (e.g. `.xml`) and makes errors point at the right file.
+   let tpl = env
+      .template_from_named_str(source.path.as_str(), &source.contents)
+      .map_err(|source_err| Error::CouldNotAddTemplate {
+         source: source_err,
+         path: source.path.clone(),
+      })?;
+
+   tpl.render_to_write(
+      Context {
+         config: site,
+         items,
+         archive,
+         source,
+      },
+      into,
+   )
+   .map(|_state| ())
+   .map_err(|source_err| Error::Render {
+      source: source_err,
+      path: source.path.clone(),
+   })
+}
+
+/// Render a page which does not correspond to any single source file, e.g. the page for
+/// a taxonomy term. The `path` is the output location, used for reporting errors.
+pub fn render_view(
+   env: &Environment,
+   layout: &str,
+   context: impl Serialize,
+   path: &Utf8Path,
+   into: impl Write,
+) -> Result<(), Error> {
+   debug!("Rendering view '{path}' with layout '{layout}'");
+
+   let tpl = env
+      .get_template(layout)
+      .map_err(|source| Error::MissingTemplate {
+         source,
+         path: path.to_owned(),
+      })?;
+
+   tpl.render_to_write(context, into)
+      .map(|_state| ())
+      .map_err(|source| Error::Render {
+         source,
+         path: path.to_owned(),
+      })
+}
+
+#[cfg(test)]
+mod tests {
+   use super::*;
+   use crate::{archive::Order, test_support};
+
+   #[test]
+   fn templated_outputs_get_item_content_as_html() {
+      let config = test_support::config();
+
+      let post = test_support::source(
+         "post.md",
+         "---\ntitle: A post\ndate: 2025-06-22T09:15:00Z\n---\n\nSome *text*.\n",
+      );
+      let items = vec![test_support::item(&post)];
+      let archive = Archive::new(&items, Order::NewFirst).unwrap();
+
+      let templated = test_support::source(
+         "content.lx.txt",
+         "{% for item in items %}{{ item.content }}{% endfor %}",
+      );
+      let mut buf = Vec::new();
+      render_templated(
+         &Environment::new(),
+         &templated,
+         &items,
+         &archive,
+         &config,
+         &mut buf,
+      )
+      .unwrap();
+
+      assert_eq!(
+         String::from_utf8(buf).unwrap(),
+         "<p>Some <em>text</em>.</p>\n"
+      );
+   }
+}
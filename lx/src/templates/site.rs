@@ -0,0 +1,131 @@
+//! The whole site, available to every template as the `site` global.
+//!
+//! Templates can use `site.items` and `site.archive` directly, or query the items:
+//!
+//! - `site.recent(n)`: the `n` most recent posts
+//! - `site.featured()`: every featured item
+//! - `site.by_tag(tag)`: every item with the given tag
+//! - `site.in_dir(path)`: every item whose source is in the given directory (relative
+//!   to the content root), including its subdirectories
+//!
+//! Every query returns posts newest first, followed by pages.
+
+use std::sync::Arc;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use chrono::{DateTime, FixedOffset};
+use minijinja::{
+   Error, ErrorKind, State, Value,
+   value::{Enumerator, Object, from_args},
+};
+
+use crate::{archive::Archive, page::Item};
+
+#[derive(Debug)]
+pub struct Site {
+   entries: Vec<Entry>,
+   archive: Value,
+}
+
+/// An item, already converted for templates, along with the data needed to query it.
+#[derive(Debug)]
+struct Entry {
+   value: Value,
+   date: Option<DateTime<FixedOffset>>,
+   featured: bool,
+   tags: Vec<String>,
+   dir: Utf8PathBuf,
+}
+
+impl Site {
+   pub fn new(items: &[Item], archive: &Archive, content_dir: &Utf8Path) -> Site {
+      let mut entries = items
+         .iter()
+         .map(|item| Entry {
+            value: Value::from_serialize(item),
+            date: match item {
+               Item::Post(post) => Some(post.date),
+               Item::Page(_) => None,
+            },
+            featured: item.data().featured,
+            tags: item.data().tags.clone(),
+            dir: item
+               .source()
+               .path
+               .parent()
+               .and_then(|dir| dir.strip_prefix(content_dir).ok())
+               .map(Utf8Path::to_owned)
+               .unwrap_or_default(),
+         })
+         .collect::<Vec<_>>();
+
+      // Newest posts first; pages have no date, so they sort after all posts.
+      entries.sort_by(|a, b| b.date.cmp(&a.date));
+
+      Site {
+         entries,
+         archive: Value::from_serialize(archive),
+      }
+   }
+
+   fn matching(&self, predicate: impl Fn(&Entry) -> bool) -> Value {
+      self
+         .entries
+         .iter()
+         .filter(|entry| predicate(entry))
+         .map(|entry| entry.value.clone())
+         .collect()
+   }
+}
+
+impl Object for Site {
+   fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+      match key.as_str()? {
+         "items" => Some(self.matching(|_| true)),
+         "archive" => Some(self.archive.clone()),
+         _ => None,
+      }
+   }
+
+   fn enumerate(self: &Arc<Self>) -> Enumerator {
+      Enumerator::Str(&["items", "archive"])
+   }
+
+   fn call_method(
+      self: &Arc<Self>,
+      _state: &State<'_, '_>,
+      method: &str,
+      args: &[Value],
+   ) -> Result<Value, Error> {
+      match method {
+         "recent" => {
+            let (count,): (usize,) = from_args(args)?;
+            Ok(self
+               .entries
+               .iter()
+               .filter(|entry| entry.date.is_some())
+               .take(count)
+               .map(|entry| entry.value.clone())
+               .collect())
+         }
+
+         "featured" => {
+            let () = from_args(args)?;
+            Ok(self.matching(|entry| entry.featured))
+         }
+
+         "by_tag" => {
+            let (tag,): (&str,) = from_args(args)?;
+            Ok(self.matching(|entry| entry.tags.iter().any(|t| t == tag)))
+         }
+
+         "in_dir" => {
+            let (dir,): (&str,) = from_args(args)?;
+            let dir = Utf8Path::new(dir.trim_matches('/'));
+            Ok(self.matching(|entry| entry.dir.starts_with(dir)))
+         }
+
+         _ => Err(Error::from(ErrorKind::UnknownMethod)),
+      }
+   }
+}
@@ -0,0 +1,56 @@
+//! Fixtures shared by the tests throughout the crate.
+
+use camino::{Utf8Path, Utf8PathBuf};
+use lx_md::Markdown;
+
+use crate::{
+   data::{config::Config, item::cascade::Cascade},
+   page::{self, Item, Rendered, Source},
+};
+
+/// The content directory of the site which test sources live in.
+pub const CONTENT_DIR: &str = "/site/content";
+
+const CONFIG: &str = "
+url: https://example.com/
+repo: https://example.com/repo
+title: Example
+description: An example
+author: { name: Example, email: hi@example.com, links: {} }
+output: public
+image: { url: https://example.com/image.png }
+";
+
+/// A site config with just the required fields.
+pub fn config() -> Config {
+   config_with("")
+}
+
+/// A site config with the required fields, plus whatever other top-level fields are in
+/// `extra`.
+pub fn config_with(extra: &str) -> Config {
+   serde_yaml::from_str(&format!("{CONFIG}{extra}")).unwrap()
+}
+
+/// A source file at `path`, relative to the content directory.
+pub fn source(path: &str, contents: &str) -> Source {
+   Source {
+      path: Utf8PathBuf::from(CONTENT_DIR).join(path),
+      contents: contents.to_owned(),
+   }
+}
+
+/// Prepare and render a source, without any cascade, taxonomies, or templating.
+pub fn rendered(source: &Source) -> Rendered {
+   let md = Markdown::new(None);
+   let cascade = Cascade::new(&[]).unwrap();
+   page::prepare(&md, source, &cascade, &[])
+      .unwrap()
+      .render(&md, |text, _| Ok(text.to_owned()))
+      .unwrap()
+}
+
+/// The item for a source, as the build would produce it.
+pub fn item(source: &Source) -> Item<'_> {
+   Item::from_rendered(rendered(source), source, Utf8Path::new(CONTENT_DIR)).unwrap()
+}